@@ -0,0 +1,257 @@
+use std::{borrow::Cow, ops::Bound};
+
+use surrealdb::sql::{
+    Block, Data, Entry, Expression, Field, Id, Idiom, Part, Statement, Subquery, Value,
+};
+
+///
+/// Convert a subquery into the statement it wraps, returning
+/// nothing for subqueries which are not supported
+///
+pub fn subquery_statement(subquery: &Subquery) -> Option<Statement> {
+    let statement = match subquery {
+        Subquery::Value(value) => Statement::Value(value.clone()),
+        Subquery::Ifelse(ifelse) => Statement::Ifelse(ifelse.clone()),
+        Subquery::Output(output) => Statement::Output(output.clone()),
+        Subquery::Select(select) => Statement::Select(select.clone()),
+        Subquery::Create(create) => Statement::Create(create.clone()),
+        Subquery::Update(update) => Statement::Update(update.clone()),
+        Subquery::Delete(delete) => Statement::Delete(delete.clone()),
+        Subquery::Relate(relate) => Statement::Relate(relate.clone()),
+        Subquery::Insert(insert) => Statement::Insert(insert.clone()),
+        #[allow(unreachable_patterns)]
+        _ => return None,
+    };
+
+    Some(statement)
+}
+
+///
+/// Convert the entries of a block into the statements they wrap,
+/// using nothing for entries which are not supported
+///
+pub fn block_statements(block: &Block) -> Vec<Option<Statement>> {
+    block
+        .0
+        .iter()
+        .map(|entry| {
+            let statement = match entry {
+                Entry::Value(value) => Statement::Value(value.clone()),
+                Entry::Set(set) => Statement::Set(set.clone()),
+                Entry::Ifelse(ifelse) => Statement::Ifelse(ifelse.clone()),
+                Entry::Output(output) => Statement::Output(output.clone()),
+                Entry::Select(select) => Statement::Select(select.clone()),
+                Entry::Create(create) => Statement::Create(create.clone()),
+                Entry::Update(update) => Statement::Update(update.clone()),
+                Entry::Delete(delete) => Statement::Delete(delete.clone()),
+                Entry::Relate(relate) => Statement::Relate(relate.clone()),
+                Entry::Insert(insert) => Statement::Insert(insert.clone()),
+                Entry::Define(define) => Statement::Define(define.clone()),
+                Entry::Remove(remove) => Statement::Remove(remove.clone()),
+                #[allow(unreachable_patterns)]
+                _ => return None,
+            };
+
+            Some(statement)
+        })
+        .collect()
+}
+
+fn idiom_value(idiom: &Idiom) -> Cow<Value> {
+    Cow::Owned(Value::Idiom(idiom.clone()))
+}
+
+fn data_values(data: &Data) -> Vec<Cow<Value>> {
+    match data {
+        Data::SetExpression(sets) | Data::UpdateExpression(sets) => sets
+            .iter()
+            .flat_map(|(idiom, _, value)| [idiom_value(idiom), Cow::Borrowed(value)])
+            .collect(),
+        Data::UnsetExpression(idioms) => idioms.iter().map(idiom_value).collect(),
+        Data::ValuesExpression(rows) => rows
+            .iter()
+            .flatten()
+            .flat_map(|(idiom, value)| [idiom_value(idiom), Cow::Borrowed(value)])
+            .collect(),
+        Data::PatchExpression(value)
+        | Data::MergeExpression(value)
+        | Data::ContentExpression(value)
+        | Data::SingleExpression(value) => vec![Cow::Borrowed(value)],
+        _ => Vec::new(),
+    }
+}
+
+///
+/// Returns the values contained in the clauses of a statement, or nothing
+/// when the statement is not known to contain only these values
+///
+pub fn statement_values(statement: &Statement) -> Option<Vec<Cow<Value>>> {
+    let mut values = Vec::new();
+
+    match statement {
+        Statement::Value(value) => values.push(Cow::Borrowed(value)),
+        Statement::Set(set) => values.push(Cow::Borrowed(&set.what)),
+        Statement::Output(output) => values.push(Cow::Borrowed(&output.what)),
+        Statement::Ifelse(ifelse) => {
+            for (cond, then) in &ifelse.exprs {
+                values.push(Cow::Borrowed(cond));
+                values.push(Cow::Borrowed(then));
+            }
+
+            values.extend(ifelse.close.iter().map(Cow::Borrowed));
+        }
+        Statement::Select(select) => {
+            for field in &select.expr.0 {
+                if let Field::Single { expr, .. } = field {
+                    values.push(Cow::Borrowed(expr));
+                }
+            }
+
+            values.extend(select.what.0.iter().map(Cow::Borrowed));
+            values.extend(select.cond.iter().map(|cond| Cow::Borrowed(&cond.0)));
+            values.extend(
+                select
+                    .split
+                    .iter()
+                    .flat_map(|s| s.0.iter().map(|s| idiom_value(&s.0))),
+            );
+            values.extend(
+                select
+                    .group
+                    .iter()
+                    .flat_map(|g| g.0.iter().map(|g| idiom_value(&g.0))),
+            );
+            values.extend(
+                select
+                    .order
+                    .iter()
+                    .flat_map(|o| o.0.iter().map(|o| idiom_value(&o.order))),
+            );
+            values.extend(select.limit.iter().map(|limit| Cow::Borrowed(&limit.0)));
+            values.extend(select.start.iter().map(|start| Cow::Borrowed(&start.0)));
+            values.extend(
+                select
+                    .fetch
+                    .iter()
+                    .flat_map(|f| f.0.iter().map(|f| idiom_value(&f.0))),
+            );
+        }
+        Statement::Live(live) => {
+            for field in &live.expr.0 {
+                if let Field::Single { expr, .. } = field {
+                    values.push(Cow::Borrowed(expr));
+                }
+            }
+
+            values.push(Cow::Borrowed(&live.what));
+            values.extend(live.cond.iter().map(|cond| Cow::Borrowed(&cond.0)));
+        }
+        Statement::Create(create) => {
+            values.extend(create.what.0.iter().map(Cow::Borrowed));
+            values.extend(create.data.iter().flat_map(data_values));
+        }
+        Statement::Update(update) => {
+            values.extend(update.what.0.iter().map(Cow::Borrowed));
+            values.extend(update.data.iter().flat_map(data_values));
+            values.extend(update.cond.iter().map(|cond| Cow::Borrowed(&cond.0)));
+        }
+        Statement::Delete(delete) => {
+            values.extend(delete.what.0.iter().map(Cow::Borrowed));
+            values.extend(delete.cond.iter().map(|cond| Cow::Borrowed(&cond.0)));
+        }
+        Statement::Relate(relate) => {
+            values.push(Cow::Borrowed(&relate.kind));
+            values.push(Cow::Borrowed(&relate.from));
+            values.push(Cow::Borrowed(&relate.with));
+            values.extend(relate.data.iter().flat_map(data_values));
+        }
+        Statement::Insert(insert) => {
+            values.push(Cow::Borrowed(&insert.into));
+            values.extend(data_values(&insert.data));
+            values.extend(insert.update.iter().flat_map(data_values));
+        }
+        _ => return None,
+    }
+
+    Some(values)
+}
+
+fn id_values(id: &Id) -> Option<Vec<Cow<Value>>> {
+    match id {
+        Id::Array(array) => Some(array.iter().map(Cow::Borrowed).collect()),
+        Id::Object(object) => Some(object.values().map(Cow::Borrowed).collect()),
+        Id::Number(_) | Id::String(_) | Id::Generate(_) => Some(Vec::new()),
+        #[allow(unreachable_patterns)]
+        _ => None,
+    }
+}
+
+fn idiom_values(idiom: &Idiom) -> Option<Vec<Cow<Value>>> {
+    let mut values = Vec::new();
+
+    for part in &idiom.0 {
+        match part {
+            Part::Where(value) | Part::Value(value) | Part::Start(value) => {
+                values.push(Cow::Borrowed(value))
+            }
+            Part::Graph(graph) => values.extend(graph.cond.iter().map(|c| Cow::Borrowed(&c.0))),
+            Part::All
+            | Part::Flatten
+            | Part::Last
+            | Part::First
+            | Part::Field(_)
+            | Part::Index(_) => {}
+            #[allow(unreachable_patterns)]
+            _ => return None,
+        }
+    }
+
+    Some(values)
+}
+
+///
+/// Returns the values nested in a value, excluding subqueries and blocks,
+/// or nothing when the value is not known to contain only these values
+///
+pub fn value_children(value: &Value) -> Option<Vec<Cow<Value>>> {
+    let children = match value {
+        Value::None
+        | Value::Null
+        | Value::Bool(_)
+        | Value::Number(_)
+        | Value::Strand(_)
+        | Value::Duration(_)
+        | Value::Datetime(_)
+        | Value::Uuid(_)
+        | Value::Regex(_)
+        | Value::Constant(_)
+        | Value::Geometry(_)
+        | Value::Table(_)
+        | Value::Param(_) => Vec::new(),
+        Value::Array(array) => array.iter().map(Cow::Borrowed).collect(),
+        Value::Object(object) => object.values().map(Cow::Borrowed).collect(),
+        Value::Expression(expression) => match expression.as_ref() {
+            Expression::Unary { v, .. } => vec![Cow::Borrowed(v)],
+            Expression::Binary { l, r, .. } => vec![Cow::Borrowed(l), Cow::Borrowed(r)],
+        },
+        Value::Cast(cast) => vec![Cow::Borrowed(&cast.1)],
+        Value::Function(function) => function.args().iter().map(Cow::Borrowed).collect(),
+        Value::Idiom(idiom) => idiom_values(idiom)?,
+        Value::Thing(thing) => id_values(&thing.id)?,
+        Value::Edges(edges) => id_values(&edges.from.id)?,
+        Value::Range(range) => {
+            let mut children = Vec::new();
+
+            for bound in [&range.beg, &range.end] {
+                if let Bound::Included(id) | Bound::Excluded(id) = bound {
+                    children.extend(id_values(id)?);
+                }
+            }
+
+            children
+        }
+        _ => return None,
+    };
+
+    Some(children)
+}
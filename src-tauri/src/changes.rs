@@ -0,0 +1,99 @@
+use serde::Serialize;
+use surrealdb::sql::{Table, Value};
+
+use crate::{error::Error, query::ConnectionState};
+
+#[derive(Serialize)]
+pub struct ChangeEntry {
+    pub versionstamp: u64,
+    pub operation: String,
+    pub diff: serde_json::Value,
+}
+
+#[derive(Serialize)]
+pub struct ChangePage {
+    pub changes: Vec<ChangeEntry>,
+    pub next: Option<u64>,
+}
+
+fn parse_versionstamp(value: Option<&Value>) -> u64 {
+    match value {
+        Some(Value::Number(n)) => n.as_int() as u64,
+        _ => 0,
+    }
+}
+
+///
+/// Flatten the change sets returned by SHOW CHANGES into individual
+/// entries, each tagged with the versionstamp of its change set
+///
+fn parse_change_sets(result: Value) -> Vec<ChangeEntry> {
+    let mut entries = Vec::new();
+
+    let sets = match result {
+        Value::Array(sets) => sets,
+        _ => return entries,
+    };
+
+    for set in sets {
+        let set = match set {
+            Value::Object(set) => set,
+            _ => continue,
+        };
+
+        let versionstamp = parse_versionstamp(set.get("versionstamp"));
+        let changes = match set.get("changes") {
+            Some(Value::Array(changes)) => changes.clone(),
+            _ => continue,
+        };
+
+        for change in changes {
+            if let Value::Object(change) = change {
+                for (operation, diff) in change {
+                    entries.push(ChangeEntry {
+                        versionstamp,
+                        operation,
+                        diff: diff.into_json(),
+                    });
+                }
+            }
+        }
+    }
+
+    entries
+}
+
+#[tauri::command]
+pub async fn fetch_changes(
+    connection_id: String,
+    table: String,
+    since: u64,
+    limit: u32,
+    state: tauri::State<'_, ConnectionState>,
+) -> Result<ChangePage, Error> {
+    let client = state.client(&connection_id).await?;
+
+    let query = format!(
+        "SHOW CHANGES FOR TABLE {} SINCE {} LIMIT {}",
+        Table::from(table),
+        since,
+        limit
+    );
+
+    let mut response = client.query(query).await?;
+    let result: Value = response.take(0)?;
+
+    let set_count = match &result {
+        Value::Array(sets) => sets.len(),
+        _ => 0,
+    };
+
+    let changes = parse_change_sets(result);
+    let next = if set_count >= limit as usize {
+        changes.last().map(|c| c.versionstamp + 1)
+    } else {
+        None
+    };
+
+    Ok(ChangePage { changes, next })
+}
@@ -6,14 +6,16 @@ use std::{
 };
 use tauri::api::path::home_dir;
 
+use crate::error::Error;
+
 const DEFAULT_CONFIG: &str = "{}";
 
-fn get_config_path() -> String {
+fn get_config_path() -> PathBuf {
     let mut base_dir = home_dir().unwrap_or_else(|| PathBuf::from("."));
 
     base_dir.push(".surrealist.json");
     if base_dir.exists() {
-        return base_dir.to_str().unwrap().to_string();
+        return base_dir;
     }
 
     let config_dir_result = env::var("XDG_CONFIG_HOME");
@@ -22,41 +24,65 @@ fn get_config_path() -> String {
             let mut config = PathBuf::from(value);
             config.push("surrealist");
             config.push("config.json");
-            config.to_str().unwrap().to_string()
+            config
         }
-        Err(_) => base_dir.to_str().unwrap().to_string(),
+        Err(_) => base_dir,
     };
 }
 
+pub fn get_history_path() -> PathBuf {
+    get_config_path().with_extension("history")
+}
+
 #[tauri::command]
-pub fn load_config() -> String {
+pub fn load_config() -> Result<String, Error> {
     let read_op = File::open(get_config_path());
     let mut result = String::new();
 
     match read_op {
         Ok(mut file) => {
-            file.read_to_string(&mut result)
-                .expect("config should be readable");
+            file.read_to_string(&mut result)?;
         }
         Err(_) => {
-            save_config(DEFAULT_CONFIG);
+            save_config(DEFAULT_CONFIG)?;
             result = DEFAULT_CONFIG.to_string();
         }
     }
 
-    result
+    Ok(result)
+}
+
+fn write_config(path: &Path, config: &str) -> Result<(), Error> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut write_op = File::create(path)?;
+
+    write_op.write_all(config.as_bytes())?;
+
+    Ok(())
 }
 
 #[tauri::command]
-pub fn save_config(config: &str) {
-    let path_string = get_config_path();
-    let path = Path::new(&path_string);
-    let parent = path.parent().unwrap();
-    fs::create_dir_all(parent).expect("config directory should be writable");
+pub fn save_config(config: &str) -> Result<(), Error> {
+    write_config(&get_config_path(), config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_config_to_unwritable_path_returns_io_error() {
+        let file = env::temp_dir().join("surrealist-config-test");
 
-    let mut write_op = File::create(get_config_path()).unwrap();
+        fs::write(&file, "").unwrap();
 
-    write_op
-        .write_all(config.as_bytes())
-        .expect("config should be writable");
+        let result = write_config(&file.join("config.json"), DEFAULT_CONFIG);
+
+        fs::remove_file(&file).unwrap();
+
+        assert!(matches!(result, Err(Error::Io(_))));
+    }
 }
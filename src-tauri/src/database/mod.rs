@@ -5,12 +5,26 @@ use std::{
     thread,
     time::Instant,
 };
+
+use serde::Serialize;
 use tauri::Manager;
 
+use crate::error::Error;
+
 mod shell;
 
 pub struct DatabaseState(pub Mutex<Option<Child>>);
 
+///
+/// Emit an event from the output thread, logging events which
+/// could not be delivered instead of aborting the thread
+///
+fn emit_event<S: Serialize + Clone>(window: &tauri::Window, event: &str, payload: S) {
+    if let Err(err) = window.emit(event, payload) {
+        println!("Failed to deliver {} event: {}", event, err);
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 #[tauri::command]
 pub fn start_database(
@@ -22,46 +36,44 @@ pub fn start_database(
     driver: &str,
     storage: &str,
     executable: &str,
-) -> Result<(), String> {
-    let mut process = state.0.lock().unwrap();
+) -> Result<(), Error> {
+    let mut process = state
+        .0
+        .lock()
+        .map_err(|_| Error::Invalid("Database state is unavailable".to_owned()))?;
     let start_at = Instant::now();
 
     if process.is_some() {
-        return Err("Database already running".to_owned());
+        return Err(Error::Invalid("Database already running".to_owned()));
     }
 
     let child_result = start_surreal_process(username, password, port, driver, storage, executable);
     let mut child_proc = match child_result {
         Ok(child) => child,
         Err(err) => {
-            window
-                .emit("database:error", err)
-                .expect("error result should be delivered");
+            window.emit("database:error", err.to_string())?;
 
-            return Err("Failed to start database".to_owned());
+            return Err(err);
         }
     };
 
-    let output = child_proc.stderr.take().unwrap();
+    let output = child_proc
+        .stderr
+        .take()
+        .ok_or_else(|| Error::ProcessSpawn("Missing database output".to_owned()))?;
 
     *process = Some(child_proc);
 
-    window
-        .emit("database:start", true)
-        .expect("start result should be delivered");
+    window.emit("database:start", true)?;
 
     thread::spawn(move || {
         let reader = BufReader::new(output);
         let mut has_started = false;
 
-        for line in reader.lines() {
-            let message = line.unwrap();
-
+        for message in reader.lines().map_while(Result::ok) {
             println!("Surreal: {}", message);
 
-            window
-                .emit("database:output", message)
-                .expect("console output should be delivered");
+            emit_event(&window, "database:output", message);
 
             has_started = true;
         }
@@ -70,39 +82,44 @@ pub fn start_database(
 
         if elapsed <= 500 {
             if !has_started {
-                window.emit("database:output", "SurrealDB did not start. Are you sure the Surreal executable is available?").expect("console output should be delivered");
+                emit_event(
+                    &window,
+                    "database:output",
+                    "SurrealDB did not start. Are you sure the Surreal executable is available?",
+                );
             }
 
-            window
-                .emit(
-                    "database:error",
-                    "SurrealDB did not start correctly, check the console for more information",
-                )
-                .expect("error result should be delivered");
+            emit_event(
+                &window,
+                "database:error",
+                "SurrealDB did not start correctly, check the console for more information",
+            );
         } else {
-            window
-                .emit("database:stop", true)
-                .expect("stop result should be delivered");
+            emit_event(&window, "database:stop", true);
         }
 
         let handle = window.app_handle();
         let state = handle.state::<DatabaseState>();
-        let mut process = state.0.lock().unwrap();
-
-        *process = None;
+        if let Ok(mut process) = state.0.lock() {
+            *process = None;
+        }
     });
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn stop_database(state: tauri::State<DatabaseState>) -> Result<bool, String> {
-    let process = state.0.lock().unwrap().take();
+pub fn stop_database(state: tauri::State<DatabaseState>) -> Result<bool, Error> {
+    let process = state
+        .0
+        .lock()
+        .map_err(|_| Error::Invalid("Database state is unavailable".to_owned()))?
+        .take();
 
     match process {
         None => Ok(false),
         Some(child) => {
-            kill_surreal_process(child.id());
+            kill_surreal_process(child.id())?;
 
             Ok(true)
         }
@@ -112,16 +129,15 @@ pub fn stop_database(state: tauri::State<DatabaseState>) -> Result<bool, String>
 ///
 /// Kill the process with the given id
 ///
-pub fn kill_surreal_process(id: u32) {
+pub fn kill_surreal_process(id: u32) -> Result<(), Error> {
     let shell_cmd = shell::build_kill_command(&id);
     let mut cmd_chain = Command::new(&shell_cmd[0]);
 
     shell::configure_command(&mut cmd_chain);
 
-    cmd_chain
-        .args(&shell_cmd[1..])
-        .output()
-        .expect("surreal process should be killed");
+    cmd_chain.args(&shell_cmd[1..]).output()?;
+
+    Ok(())
 }
 
 ///
@@ -134,7 +150,7 @@ pub fn start_surreal_process(
     driver: &str,
     storage: &str,
     executable: &str,
-) -> Result<Child, String> {
+) -> Result<Child, Error> {
     let bind_addr = format!("0.0.0.0:{}", port);
     let path = if executable.is_empty() {
         "surreal"
@@ -153,7 +169,7 @@ pub fn start_surreal_process(
         "memory" => args.push("memory"),
         "file" => args.push(file_uri.as_str()),
         "tikv" => args.push(tikv_uri.as_str()),
-        _ => Err("Invalid database driver")?,
+        _ => Err(Error::Invalid("Invalid database driver".to_owned()))?,
     }
 
     args.push("--allow-all");
@@ -170,7 +186,7 @@ pub fn start_surreal_process(
         .stdout(Stdio::null())
         .stderr(Stdio::piped())
         .spawn()
-        .expect("surreal process should be spawned");
+        .map_err(|err| Error::ProcessSpawn(err.to_string()))?;
 
     Ok(child_proc)
 }
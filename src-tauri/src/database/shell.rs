@@ -11,10 +11,9 @@ pub fn build_start_command(args: Vec<&str>) -> Vec<String> {
 
         configure_command(&mut cmd_chain);
 
-        cmd_chain
-            .args(vec!["/IM", "surreal.exe", "/F"])
-            .output()
-            .expect("surreal process should be killed");
+        if let Err(err) = cmd_chain.args(vec!["/IM", "surreal.exe", "/F"]).output() {
+            println!("Failed to kill existing surreal.exe processes: {}", err);
+        }
     }
 
     vec!["cmd".to_owned(), "/c".to_owned(), args.join(" ")]
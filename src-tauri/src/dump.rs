@@ -0,0 +1,273 @@
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+};
+
+use serde::Serialize;
+use surrealdb::{
+    engine::any::Any,
+    sql::{parse, Statement, Table, Value},
+    Surreal,
+};
+
+use crate::{error::Error, query::ConnectionState};
+
+const EXPORT_BATCH_SIZE: usize = 1000;
+const IMPORT_BATCH_SIZE: usize = 100;
+
+const DATABASE_DEFINITIONS: [&str; 6] = [
+    "tokens",
+    "params",
+    "functions",
+    "analyzers",
+    "scopes",
+    "users",
+];
+
+const TABLE_DEFINITIONS: [&str; 3] = ["fields", "indexes", "events"];
+
+#[derive(Clone, Serialize)]
+pub struct DumpProgress {
+    pub dump_id: String,
+    pub processed: usize,
+    pub total: usize,
+}
+
+fn write_section(writer: &mut impl Write, title: &str) -> Result<(), Error> {
+    writeln!(writer, "-- ------------------------------")?;
+    writeln!(writer, "-- {}", title)?;
+    writeln!(writer, "-- ------------------------------")?;
+    writeln!(writer)?;
+
+    Ok(())
+}
+
+///
+/// Retrieve the definitions listed under the given key of an INFO result
+///
+fn collect_definitions(info: &Value, key: &str) -> Vec<(String, String)> {
+    let mut definitions = Vec::new();
+
+    if let Value::Object(info) = info {
+        if let Some(Value::Object(entries)) = info.get(key) {
+            for (name, definition) in entries.iter() {
+                if let Value::Strand(definition) = definition {
+                    definitions.push((name.to_owned(), definition.as_str().to_owned()));
+                }
+            }
+        }
+    }
+
+    definitions
+}
+
+async fn fetch_info(client: &Surreal<Any>, query: String) -> Result<Value, Error> {
+    Ok(client.query(query).await?.take(0)?)
+}
+
+///
+/// Write all records of a table as statements, paging
+/// through the table to keep memory usage bounded
+///
+async fn write_table_data(
+    writer: &mut impl Write,
+    client: &Surreal<Any>,
+    table: &str,
+) -> Result<(), Error> {
+    let mut start = 0;
+
+    loop {
+        let mut response = client
+            .query("SELECT * FROM type::table($table) LIMIT $limit START $start")
+            .bind(("table", table))
+            .bind(("limit", EXPORT_BATCH_SIZE))
+            .bind(("start", start))
+            .await?;
+
+        let records: Value = response.take(0)?;
+        let records = match records {
+            Value::Array(records) => records.0,
+            _ => Vec::new(),
+        };
+
+        if records.is_empty() {
+            break;
+        }
+
+        writeln!(writer, "BEGIN TRANSACTION;")?;
+
+        for record in &records {
+            let object = match record {
+                Value::Object(object) => object,
+                _ => continue,
+            };
+
+            let id = match object.get("id") {
+                Some(id) => id,
+                None => continue,
+            };
+
+            match (object.get("in"), object.get("out")) {
+                (Some(Value::Thing(from)), Some(Value::Thing(with))) => writeln!(
+                    writer,
+                    "RELATE {} -> {} -> {} CONTENT {};",
+                    from, id, with, record
+                )?,
+                _ => writeln!(writer, "UPDATE {} CONTENT {};", id, record)?,
+            }
+        }
+
+        writeln!(writer, "COMMIT TRANSACTION;")?;
+        writeln!(writer)?;
+
+        if records.len() < EXPORT_BATCH_SIZE {
+            break;
+        }
+
+        start += EXPORT_BATCH_SIZE;
+    }
+
+    Ok(())
+}
+
+///
+/// Write a SurrealQL dump of the selected namespace and database to a file,
+/// optionally limited to the schema or to a subset of the tables
+///
+#[tauri::command]
+pub async fn export_database(
+    connection_id: String,
+    path: String,
+    schema_only: bool,
+    tables: Option<Vec<String>>,
+    state: tauri::State<'_, ConnectionState>,
+) -> Result<(), Error> {
+    let client = state.client(&connection_id).await?;
+
+    println!("Exporting database to {}", path);
+
+    let file = File::create(&path)?;
+    let mut writer = BufWriter::new(file);
+    let db_info = fetch_info(&client, "INFO FOR DB".to_owned()).await?;
+
+    write_section(&mut writer, "OPTION")?;
+    writeln!(writer, "OPTION IMPORT;")?;
+    writeln!(writer)?;
+
+    for key in DATABASE_DEFINITIONS {
+        let definitions = collect_definitions(&db_info, key);
+
+        if definitions.is_empty() {
+            continue;
+        }
+
+        write_section(&mut writer, &key.to_uppercase())?;
+
+        for (_, definition) in definitions {
+            writeln!(writer, "{};", definition)?;
+        }
+
+        writeln!(writer)?;
+    }
+
+    let table_definitions = collect_definitions(&db_info, "tables")
+        .into_iter()
+        .filter(|(name, _)| tables.as_ref().map_or(true, |t| t.contains(name)))
+        .collect::<Vec<(String, String)>>();
+
+    for (name, definition) in &table_definitions {
+        let query = format!("INFO FOR TABLE {}", Table::from(name.as_str()));
+        let table_info = fetch_info(&client, query).await?;
+
+        write_section(&mut writer, &format!("TABLE: {}", name))?;
+        writeln!(writer, "{};", definition)?;
+
+        for key in TABLE_DEFINITIONS {
+            for (_, definition) in collect_definitions(&table_info, key) {
+                writeln!(writer, "{};", definition)?;
+            }
+        }
+
+        writeln!(writer)?;
+    }
+
+    if !schema_only {
+        for (name, _) in &table_definitions {
+            write_section(&mut writer, &format!("TABLE DATA: {}", name))?;
+            write_table_data(&mut writer, &client, name).await?;
+        }
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+///
+/// Replay a SurrealQL dump against the connection in batches of statements,
+/// keeping transactions intact and reporting progress after every batch
+///
+#[tauri::command]
+pub async fn import_database(
+    window: tauri::Window,
+    connection_id: String,
+    dump_id: String,
+    path: String,
+    state: tauri::State<'_, ConnectionState>,
+) -> Result<usize, Error> {
+    let client = state.client(&connection_id).await?;
+
+    state.check_writable(&connection_id).await?;
+
+    println!("Importing database from {}", path);
+
+    let text = fs::read_to_string(&path)?;
+    let statements = parse(&text)?.0 .0;
+
+    let mut progress = DumpProgress {
+        dump_id,
+        processed: 0,
+        total: statements.len(),
+    };
+
+    let mut batch = Vec::new();
+    let mut in_transaction = false;
+    let mut import_option = false;
+
+    for (index, statement) in statements.iter().enumerate() {
+        match statement {
+            Statement::Begin(_) => in_transaction = true,
+            Statement::Commit(_) | Statement::Cancel(_) => in_transaction = false,
+            _ => {}
+        }
+
+        // Options only apply to the query they are part of, so the import
+        // option is repeated at the start of every batch instead
+        match statement {
+            Statement::Option(option) if option.name.to_raw().eq_ignore_ascii_case("IMPORT") => {
+                import_option = option.what;
+                progress.processed += 1;
+            }
+            statement => batch.push(statement.to_string()),
+        }
+
+        let is_last = index + 1 == statements.len();
+
+        if !batch.is_empty() && (is_last || (!in_transaction && batch.len() >= IMPORT_BATCH_SIZE)) {
+            let mut query = batch.join(";\n");
+
+            if import_option {
+                query.insert_str(0, "OPTION IMPORT;\n");
+            }
+
+            client.query(query).await?.check()?;
+
+            progress.processed += batch.len();
+            batch.clear();
+
+            window.emit("dump:progress", progress.clone())?;
+        }
+    }
+
+    Ok(progress.processed)
+}
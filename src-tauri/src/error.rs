@@ -0,0 +1,152 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+use serde_json::json;
+use surrealdb::error::{Api, Db};
+
+///
+/// Errors returned by Surrealist commands, serialized to the frontend
+/// as an object containing a kind, a message and optional details
+///
+#[derive(Debug)]
+pub enum Error {
+    Auth(String),
+    ConnectionRefused(String),
+    Parse {
+        message: String,
+        line: usize,
+        column: usize,
+        snippet: String,
+    },
+    Timeout(u64),
+    NotConnected(String),
+    ProcessSpawn(String),
+    Io(String),
+    Invalid(String),
+    Database(String),
+}
+
+impl Error {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Auth(_) => "auth",
+            Error::ConnectionRefused(_) => "connection_refused",
+            Error::Parse { .. } => "parse",
+            Error::Timeout(_) => "timeout",
+            Error::NotConnected(_) => "not_connected",
+            Error::ProcessSpawn(_) => "process_spawn",
+            Error::Io(_) => "io",
+            Error::Invalid(_) => "invalid",
+            Error::Database(_) => "database",
+        }
+    }
+
+    pub fn details(&self) -> serde_json::Value {
+        match self {
+            Error::Parse {
+                line,
+                column,
+                snippet,
+                ..
+            } => json!({
+                "line": line,
+                "column": column,
+                "snippet": snippet,
+            }),
+            Error::Timeout(seconds) => json!({ "seconds": seconds }),
+            Error::NotConnected(id) => json!({ "connection": id }),
+            _ => serde_json::Value::Null,
+        }
+    }
+
+    ///
+    /// Convert an error returned while signing in into an authentication
+    /// error, as remote engines report rejected credentials as API errors
+    ///
+    pub fn auth(err: surrealdb::Error) -> Self {
+        match Error::from(err) {
+            err @ (Error::Auth(_) | Error::ConnectionRefused(_)) => err,
+            err => Error::Auth(err.to_string()),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Auth(message) => write!(f, "Authentication failed: {}", message),
+            Error::ConnectionRefused(message) => write!(f, "Connection refused: {}", message),
+            Error::Parse { message, .. } => write!(f, "{}", message),
+            Error::Timeout(seconds) => write!(f, "Query timed out after {} seconds", seconds),
+            Error::NotConnected(id) => write!(f, "Connection {} is not open", id),
+            Error::ProcessSpawn(message) => write!(f, "Failed to start database: {}", message),
+            Error::Io(message) => write!(f, "I/O error: {}", message),
+            Error::Invalid(message) => write!(f, "{}", message),
+            Error::Database(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Error", 3)?;
+
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err.to_string())
+    }
+}
+
+impl From<tauri::Error> for Error {
+    fn from(err: tauri::Error) -> Self {
+        Error::Invalid(err.to_string())
+    }
+}
+
+impl From<Db> for Error {
+    fn from(err: Db) -> Self {
+        match err {
+            Db::InvalidQuery { line, char, sql } => Error::Parse {
+                message: format!(
+                    "Parse error on line {} at character {} when parsing '{}'",
+                    line, char, sql
+                ),
+                line,
+                column: char,
+                snippet: sql,
+            },
+            Db::InvalidAuth => Error::Auth(err.to_string()),
+            err => Error::Database(err.to_string()),
+        }
+    }
+}
+
+impl From<Api> for Error {
+    fn from(err: Api) -> Self {
+        let message = err.to_string();
+
+        if message.to_lowercase().contains("connection refused") {
+            Error::ConnectionRefused(message)
+        } else {
+            Error::Database(message)
+        }
+    }
+}
+
+impl From<surrealdb::Error> for Error {
+    fn from(err: surrealdb::Error) -> Self {
+        match err {
+            surrealdb::Error::Db(err) => err.into(),
+            surrealdb::Error::Api(err) => err.into(),
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use serde::Serialize;
+use surrealdb::{
+    engine::any::Any,
+    sql::{
+        parse,
+        statements::{DefineStatement, SelectStatement},
+        Explain, Expression, Idiom, Statement, Table, Value,
+    },
+    Surreal,
+};
+
+use crate::{error::Error, query::ConnectionState};
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanKind {
+    IndexScan,
+    TableScan,
+    RecordFetch,
+    Fetch,
+    Other,
+}
+
+#[derive(Serialize)]
+pub struct PlanStep {
+    pub kind: PlanKind,
+    pub operation: String,
+    pub table: Option<String>,
+    pub index: Option<String>,
+    pub count: Option<u64>,
+    pub detail: serde_json::Value,
+}
+
+#[derive(Serialize)]
+pub struct QueryPlan {
+    pub statement: String,
+    pub uses_index: bool,
+    pub full_scan: bool,
+    pub fetched: Option<u64>,
+    pub missed_indexes: Vec<String>,
+    pub steps: Vec<PlanStep>,
+}
+
+fn get_string(value: Option<&Value>) -> Option<String> {
+    match value {
+        Some(Value::Strand(s)) => Some(s.as_str().to_owned()),
+        Some(Value::Table(t)) => Some(t.0.clone()),
+        Some(Value::None) | Some(Value::Null) | None => None,
+        Some(other) => Some(other.to_string()),
+    }
+}
+
+fn parse_step(step: &Value) -> Option<PlanStep> {
+    let step = match step {
+        Value::Object(step) => step,
+        _ => return None,
+    };
+
+    let operation = get_string(step.get("operation")).unwrap_or_default();
+    let detail = step.get("detail").cloned().unwrap_or_default();
+
+    let (table, index, count) = match &detail {
+        Value::Object(detail) => {
+            let index = match detail.get("plan") {
+                Some(Value::Object(plan)) => get_string(plan.get("index")),
+                _ => None,
+            };
+
+            let count = match detail.get("count") {
+                Some(Value::Number(n)) => Some(n.as_int() as u64),
+                _ => None,
+            };
+
+            (get_string(detail.get("table")), index, count)
+        }
+        _ => (None, None, None),
+    };
+
+    let kind = match operation.as_str() {
+        "Iterate Index" => PlanKind::IndexScan,
+        "Iterate Table" => PlanKind::TableScan,
+        "Iterate Thing" => PlanKind::RecordFetch,
+        "Fetch" => PlanKind::Fetch,
+        _ => PlanKind::Other,
+    };
+
+    Some(PlanStep {
+        kind,
+        operation,
+        table,
+        index,
+        count,
+        detail: detail.into_json(),
+    })
+}
+
+fn parse_select(query: &str) -> Result<SelectStatement, Error> {
+    let mut statements = parse(query)?.0 .0;
+
+    if statements.len() != 1 {
+        return Err(Error::Invalid(
+            "Only a single statement can be explained".to_owned(),
+        ));
+    }
+
+    match statements.remove(0) {
+        Statement::Select(select) => Ok(select),
+        _ => Err(Error::Invalid(
+            "Only SELECT statements can be explained".to_owned(),
+        )),
+    }
+}
+
+///
+/// Collect the idioms referenced in a condition, without descending
+/// into subqueries as those are planned separately
+///
+fn collect_idioms(value: &Value, idioms: &mut Vec<Idiom>) {
+    match value {
+        Value::Idiom(idiom) => idioms.push(idiom.clone()),
+        Value::Expression(expression) => match expression.as_ref() {
+            Expression::Unary { v, .. } => collect_idioms(v, idioms),
+            Expression::Binary { l, r, .. } => {
+                collect_idioms(l, idioms);
+                collect_idioms(r, idioms);
+            }
+        },
+        Value::Array(array) => array.iter().for_each(|v| collect_idioms(v, idioms)),
+        Value::Object(object) => object.values().for_each(|v| collect_idioms(v, idioms)),
+        Value::Function(function) => function
+            .args()
+            .iter()
+            .for_each(|v| collect_idioms(v, idioms)),
+        _ => {}
+    }
+}
+
+///
+/// Find the indexes of a table whose fields are referenced in
+/// the condition of a statement which performed a table scan
+///
+async fn find_missed_indexes(
+    client: &Surreal<Any>,
+    table: &str,
+    idioms: &[Idiom],
+) -> Result<Vec<String>, Error> {
+    let query = format!("INFO FOR TABLE {}", Table::from(table));
+    let info: Value = client.query(query).await?.take(0)?;
+
+    let mut missed = Vec::new();
+
+    if let Value::Object(info) = info {
+        if let Some(Value::Object(indexes)) = info.get("indexes") {
+            for definition in indexes.values() {
+                let definition = match definition {
+                    Value::Strand(definition) => definition.as_str(),
+                    _ => continue,
+                };
+
+                let statement = parse(definition)
+                    .ok()
+                    .and_then(|q| q.0 .0.into_iter().next());
+
+                if let Some(Statement::Define(DefineStatement::Index(index))) = statement {
+                    if index.cols.0.iter().any(|col| idioms.contains(col)) {
+                        missed.push(index.name.to_raw());
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(missed)
+}
+
+///
+/// Run a SELECT statement with an EXPLAIN clause and
+/// convert the resulting output into a typed plan
+///
+#[tauri::command]
+pub async fn explain_query(
+    connection_id: String,
+    query: String,
+    full: bool,
+    state: tauri::State<'_, ConnectionState>,
+) -> Result<QueryPlan, Error> {
+    let client = state.client(&connection_id).await?;
+    let mut select = parse_select(&query)?;
+
+    state.check_query(&connection_id, &query).await?;
+
+    select.explain = Some(Explain(full));
+
+    let statement = select.to_string();
+    let output: Value = client.query(statement.as_str()).await?.take(0)?;
+
+    let steps: Vec<PlanStep> = match output {
+        Value::Array(steps) => steps.iter().filter_map(parse_step).collect(),
+        _ => Vec::new(),
+    };
+
+    let uses_index = steps.iter().any(|s| matches!(s.kind, PlanKind::IndexScan));
+    let full_scan = steps.iter().any(|s| matches!(s.kind, PlanKind::TableScan));
+    let fetched = steps
+        .iter()
+        .find(|s| matches!(s.kind, PlanKind::Fetch))
+        .and_then(|s| s.count);
+
+    let mut missed_indexes = Vec::new();
+
+    if let Some(cond) = &select.cond {
+        let mut idioms = Vec::new();
+
+        collect_idioms(&cond.0, &mut idioms);
+
+        let mut tables: Vec<&String> = steps
+            .iter()
+            .filter(|s| matches!(s.kind, PlanKind::TableScan))
+            .filter_map(|s| s.table.as_ref())
+            .collect();
+
+        // A table may be scanned by several steps of the plan
+        tables.sort();
+        tables.dedup();
+
+        for table in tables {
+            for index in find_missed_indexes(&client, table, &idioms).await? {
+                if !missed_indexes.contains(&index) {
+                    missed_indexes.push(index);
+                }
+            }
+        }
+    }
+
+    Ok(QueryPlan {
+        statement,
+        uses_index,
+        full_scan,
+        fetched,
+        missed_indexes,
+        steps,
+    })
+}
@@ -0,0 +1,225 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use serde::Deserialize;
+use surrealdb::sql::{Table, Value};
+
+use crate::{error::Error, query::ConnectionState};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Ndjson,
+    Json,
+    Surql,
+}
+
+///
+/// Flatten nested objects into a single level using dotted keys,
+/// encoding arrays as JSON so every value fits in a single column
+///
+fn flatten_record(prefix: &str, value: &serde_json::Value, output: &mut Vec<(String, String)>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                let path = if prefix.is_empty() {
+                    key.to_owned()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+
+                flatten_record(&path, value, output);
+            }
+        }
+        serde_json::Value::Null => output.push((prefix.to_owned(), "".to_owned())),
+        serde_json::Value::String(text) => output.push((prefix.to_owned(), text.to_owned())),
+        other => output.push((prefix.to_owned(), other.to_string())),
+    }
+}
+
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+///
+/// Records flattened into rows, collected until all columns
+/// are known so the header can be written before the rows
+///
+#[derive(Default)]
+struct CsvTable {
+    columns: Vec<String>,
+    indexes: HashMap<String, usize>,
+    rows: Vec<Vec<(usize, String)>>,
+}
+
+impl CsvTable {
+    fn push(&mut self, record: Value) {
+        let mut fields = Vec::new();
+
+        flatten_record("", &record.into_json(), &mut fields);
+
+        let row = fields
+            .into_iter()
+            .map(|(column, value)| {
+                let index = match self.indexes.get(&column) {
+                    Some(index) => *index,
+                    None => {
+                        let index = self.columns.len();
+
+                        self.indexes.insert(column.clone(), index);
+                        self.columns.push(column);
+                        index
+                    }
+                };
+
+                (index, value)
+            })
+            .collect();
+
+        self.rows.push(row);
+    }
+
+    fn write(&self, writer: &mut impl Write) -> Result<(), Error> {
+        let header = self
+            .columns
+            .iter()
+            .map(|c| escape_csv(c))
+            .collect::<Vec<String>>();
+
+        writeln!(writer, "{}", header.join(","))?;
+
+        for row in &self.rows {
+            let mut line = vec![String::new(); self.columns.len()];
+
+            for (index, value) in row {
+                line[*index] = escape_csv(value);
+            }
+
+            writeln!(writer, "{}", line.join(","))?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_ndjson(writer: &mut impl Write, records: Vec<Value>) -> Result<(), Error> {
+    for record in records {
+        let line = serde_json::to_string(&record.into_json())
+            .map_err(|err| Error::Invalid(err.to_string()))?;
+
+        writeln!(writer, "{}", line)?;
+    }
+
+    Ok(())
+}
+
+fn write_json(writer: &mut impl Write, records: Vec<Value>, written: usize) -> Result<(), Error> {
+    for (index, record) in records.into_iter().enumerate() {
+        if written + index > 0 {
+            writeln!(writer, ",")?;
+        }
+
+        serde_json::to_writer_pretty(&mut *writer, &record.into_json())
+            .map_err(|err| Error::Invalid(err.to_string()))?;
+    }
+
+    Ok(())
+}
+
+fn write_surql(
+    writer: &mut impl Write,
+    records: &[Value],
+    table: &Option<String>,
+) -> Result<(), Error> {
+    for record in records {
+        let record_table = match record {
+            Value::Object(obj) => match obj.get("id") {
+                Some(Value::Thing(thing)) => Some(thing.tb.clone()),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        let table = record_table.or_else(|| table.clone()).ok_or_else(|| {
+            Error::Invalid("Records without an id require a target table".to_owned())
+        })?;
+
+        writeln!(writer, "INSERT INTO {} {};", Table::from(table), record)?;
+    }
+
+    Ok(())
+}
+
+///
+/// Execute the given query and write the records returned by all
+/// statements to a file in the requested format
+///
+#[tauri::command]
+pub async fn export_results(
+    connection_id: String,
+    query: String,
+    variables: Option<serde_json::Value>,
+    format: ExportFormat,
+    path: String,
+    table: Option<String>,
+    state: tauri::State<'_, ConnectionState>,
+) -> Result<usize, Error> {
+    let client = state.client(&connection_id).await?;
+
+    state.check_query(&connection_id, &query).await?;
+
+    println!("Exporting results of {} to {}", query, path);
+
+    let mut response = client
+        .query(query)
+        .bind(variables.unwrap_or(serde_json::Value::Object(Default::default())))
+        .await?;
+
+    let file = File::create(&path)?;
+    let mut writer = BufWriter::new(file);
+    let mut csv = CsvTable::default();
+    let mut written = 0;
+
+    if let ExportFormat::Json = format {
+        writeln!(writer, "[")?;
+    }
+
+    // Records are written per statement instead of first being
+    // collected across all statements into a single list
+    for i in 0..response.num_statements() {
+        let records = match response.take::<Value>(i)? {
+            Value::Array(array) => array.0,
+            Value::None | Value::Null => Vec::new(),
+            other => vec![other],
+        };
+
+        let count = records.len();
+
+        match format {
+            ExportFormat::Csv => records.into_iter().for_each(|r| csv.push(r)),
+            ExportFormat::Ndjson => write_ndjson(&mut writer, records)?,
+            ExportFormat::Json => write_json(&mut writer, records, written)?,
+            ExportFormat::Surql => write_surql(&mut writer, &records, &table)?,
+        }
+
+        written += count;
+    }
+
+    match format {
+        ExportFormat::Csv => csv.write(&mut writer)?,
+        ExportFormat::Json => writeln!(writer, "\n]")?,
+        _ => {}
+    }
+
+    writer.flush()?;
+
+    Ok(written)
+}
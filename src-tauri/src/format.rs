@@ -0,0 +1,266 @@
+use surrealdb::sql::parse;
+
+use crate::{
+    error::Error,
+    lexer::{tokenize, TokenKind},
+};
+
+const INDENT: &str = "\t";
+
+const KEYWORDS: &str = "\
+    AFTER ANALYZER AND AS ASC ASSERT AT BEFORE BEGIN BM25 BY CANCEL CHANGEFEED CHANGES \
+    COLLATE COLUMNS COMMENT COMMIT CONTAINS CONTENT CREATE DATABASE DB DEFAULT DEFINE DELETE \
+    DESC DIFF DIMENSION DROP ELSE END EVENT EXPLAIN FETCH FIELD FIELDS FILTERS FLEXIBLE FOR \
+    FROM FULL FUNCTION GROUP HIGHLIGHTS IF IGNORE IN INDEX INFO INSERT INSIDE INTO IS KILL \
+    LET LIMIT LIVE LOGIN MERGE MTREE NAMESPACE NOT NS NUMERIC ON ONLY OPTION OR ORDER \
+    OUTSIDE PARALLEL PARAM PASSHASH PASSWORD PATCH PERMISSIONS RELATE REMOVE RETURN ROLES \
+    SCHEMAFULL SCHEMALESS SCOPE SEARCH SELECT SESSION SET SHOW SIGNIN SIGNUP SINCE SLEEP \
+    SPLIT START TABLE THEN TIMEOUT TOKEN TOKENIZERS TRANSACTION TYPE UNIQUE UNSET UPDATE USE \
+    USER VALUE WHEN WHERE";
+
+const CLAUSES: &str = "\
+    FROM WHERE SPLIT GROUP ORDER LIMIT START FETCH TIMEOUT PARALLEL EXPLAIN SET UNSET \
+    CONTENT MERGE PATCH RETURN TYPE VALUE ASSERT DEFAULT PERMISSIONS FOR WHEN THEN SESSION \
+    SIGNUP SIGNIN COMMENT CHANGEFEED FIELDS COLUMNS SEARCH MTREE ROLES AS";
+
+// Keywords which are followed by a name or expression rather than another clause
+const OPERAND: &str = "\
+    AND AS BY COLUMNS CONTAINS ELSE EVENT FETCH FIELD FIELDS FROM FUNCTION IF IN INDEX INSIDE \
+    INTO IS NOT ON OR OUTSIDE PARAM RETURN SCOPE SET SPLIT TABLE THEN UNSET WHEN WHERE";
+
+fn is_listed(list: &str, word: &str) -> bool {
+    list.split_whitespace().any(|item| item == word)
+}
+
+///
+/// Returns whether the token is followed by an operand, in which
+/// case the next word is a name even when it is also a keyword
+///
+fn expects_operand(previous: &str) -> bool {
+    is_listed(OPERAND, previous)
+        || matches!(
+            previous,
+            "=" | ","
+                | "("
+                | "["
+                | "{"
+                | "."
+                | "$"
+                | ":"
+                | "<"
+                | ">"
+                | "+"
+                | "-"
+                | "/"
+                | "!"
+                | "?"
+                | "~"
+                | "|"
+                | "&"
+        )
+}
+
+fn new_line(output: &mut String, depth: usize) {
+    while output.ends_with([' ', '\t']) {
+        output.pop();
+    }
+
+    output.push('\n');
+    output.push_str(&INDENT.repeat(depth));
+}
+
+///
+/// Lay out the query with one clause per line, converting keywords to
+/// uppercase unless their token index is listed in `keep_case`. Comments
+/// are preserved at their original position in the token stream. Returns
+/// the layout along with the indexes of the words which were uppercased.
+///
+fn layout(query: &str, keep_case: &[usize]) -> (String, Vec<usize>) {
+    let tokens = tokenize(query);
+    let mut output = String::new();
+    let mut uppercased = Vec::new();
+    let mut depth: usize = 0;
+    let mut space = false;
+    let mut statement_start = true;
+    let mut in_fields = false;
+    let mut previous = String::new();
+
+    for (index, token) in tokens.iter().enumerate() {
+        let next_symbol = tokens
+            .get(index + 1)
+            .filter(|next| next.kind == TokenKind::Symbol)
+            .map(|next| next.text);
+
+        match token.kind {
+            TokenKind::Space => {
+                space = true;
+                continue;
+            }
+            TokenKind::LineComment => {
+                if !output.is_empty() && !output.ends_with('\n') {
+                    output.push(' ');
+                }
+
+                output.push_str(token.text.trim_end());
+                new_line(&mut output, if statement_start { 0 } else { 1 });
+                space = false;
+                continue;
+            }
+            TokenKind::Symbol if token.text == ";" && depth == 0 => {
+                output.push(';');
+                new_line(&mut output, 0);
+                statement_start = true;
+                in_fields = false;
+                space = false;
+                continue;
+            }
+            _ => {}
+        }
+
+        let text = match token.kind {
+            TokenKind::Word => {
+                let word = token.text;
+                let upper = word.to_uppercase();
+                let is_keyword = is_listed(KEYWORDS, &upper)
+                    && next_symbol != Some(":")
+                    && !matches!(previous.as_str(), "." | "$" | ":");
+
+                if depth == 0 && is_keyword && upper == "FROM" {
+                    in_fields = false;
+                }
+
+                // Keywords are also valid field names, so the words of a SELECT
+                // field list or following an operator never start a clause
+                let is_clause = is_keyword
+                    && depth == 0
+                    && !statement_start
+                    && !in_fields
+                    && is_listed(CLAUSES, &upper)
+                    && !expects_operand(&previous);
+
+                // Only a SELECT starting a statement or projection is followed by
+                // a field list, unlike the one in PERMISSIONS FOR select
+                if depth == 0
+                    && is_keyword
+                    && upper == "SELECT"
+                    && (statement_start
+                        || matches!(previous.as_str(), "AS" | "LIVE" | "=" | "RETURN"))
+                {
+                    in_fields = true;
+                }
+
+                if is_clause {
+                    new_line(&mut output, 1);
+                    space = false;
+                }
+
+                if is_keyword && upper != word && !keep_case.contains(&index) {
+                    uppercased.push(index);
+                    upper
+                } else {
+                    word.to_string()
+                }
+            }
+            TokenKind::Symbol => {
+                if token.is_symbol("([{") {
+                    depth += 1;
+                } else if token.is_symbol(")]}") {
+                    depth = depth.saturating_sub(1);
+                }
+
+                token.text.to_string()
+            }
+            _ => token.text.to_string(),
+        };
+
+        if space && !output.is_empty() && !output.ends_with(['\n', '\t']) {
+            output.push(' ');
+        }
+
+        output.push_str(&text);
+        previous = text.to_uppercase();
+        statement_start = false;
+        space = false;
+    }
+
+    (output.trim_end().to_owned() + "\n", uppercased)
+}
+
+///
+/// Format a query with consistent indentation, keyword casing and line
+/// breaks per clause. Input which fails to parse is refused, and the output
+/// is verified to parse to the same statements as the input.
+///
+#[tauri::command(async)]
+pub fn format_query(query: &str) -> Result<String, Error> {
+    let expected = parse(query)?.to_string();
+    let verify =
+        |formatted: &str| parse(formatted).map(|q| q.to_string()).ok().as_ref() == Some(&expected);
+
+    let (formatted, uppercased) = layout(query, &[]);
+
+    if verify(&formatted) {
+        return Ok(formatted);
+    }
+
+    // Keywords are also valid identifiers, so words which change the
+    // meaning of the query when uppercased keep their original casing
+    let keep_case = uppercased
+        .iter()
+        .copied()
+        .filter(|word| {
+            let others: Vec<usize> = uppercased.iter().copied().filter(|i| i != word).collect();
+
+            !verify(&layout(query, &others).0)
+        })
+        .collect::<Vec<usize>>();
+
+    for keep_case in [keep_case, uppercased] {
+        let (formatted, _) = layout(query, &keep_case);
+
+        if verify(&formatted) {
+            return Ok(formatted);
+        }
+    }
+
+    Err(Error::Invalid(
+        "The query could not be formatted without changing its meaning".to_owned(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permissions_are_laid_out_per_clause() {
+        let query = "define table person schemafull permissions for select where published = true \
+                     for create, update where user = $auth.id";
+
+        assert_eq!(
+            format_query(query).unwrap(),
+            "DEFINE TABLE person SCHEMAFULL\n\tPERMISSIONS\n\tFOR SELECT\n\tWHERE published = true\n\
+             \tFOR CREATE, UPDATE\n\tWHERE user = $auth.id\n"
+        );
+    }
+
+    #[test]
+    fn keyword_field_names_keep_their_line_and_case() {
+        let query = "select type, value from item where type = 'a'";
+
+        assert_eq!(
+            format_query(query).unwrap(),
+            "SELECT type, value\n\tFROM item\n\tWHERE type = 'a'\n"
+        );
+    }
+
+    #[test]
+    fn comments_are_preserved() {
+        let query = "-- people\nSELECT * FROM person /* all */ WHERE age > 18; # done";
+
+        assert_eq!(
+            format_query(query).unwrap(),
+            "-- people\nSELECT *\n\tFROM person /* all */\n\tWHERE age > 18;\n# done\n"
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use std::time::Duration;
+
+use surrealdb::{engine::any::Any, Surreal};
+use tauri::{AppHandle, Manager};
+use tokio::{task::AbortHandle, time::sleep};
+
+use crate::{
+    live::{emit_live_error, subscribe, LiveError},
+    query::{connect, ConnectionInfo, ConnectionState},
+};
+
+const PING_INTERVAL: Duration = Duration::from_secs(5);
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+async fn current_client(handle: &AppHandle, connection_id: &str) -> Option<Surreal<Any>> {
+    let state = handle.state::<ConnectionState>();
+    let instances = state.0.lock().await;
+
+    instances.get(connection_id).map(|c| c.client.clone())
+}
+
+async fn current_info(handle: &AppHandle, connection_id: &str) -> Option<ConnectionInfo> {
+    let state = handle.state::<ConnectionState>();
+    let instances = state.0.lock().await;
+
+    instances.get(connection_id).map(|c| c.info.clone())
+}
+
+///
+/// Subscribe the live queries of the connection again on the new client,
+/// reporting the live queries which could not be restored as failed
+///
+async fn restore_live_queries(
+    window: &tauri::Window,
+    handle: &AppHandle,
+    connection_id: &str,
+    client: &Surreal<Any>,
+    live_queries: Vec<(String, String)>,
+) {
+    let state = handle.state::<ConnectionState>();
+
+    for (live_id, table) in live_queries {
+        let result = subscribe(window.clone(), client, connection_id, &live_id, &table).await;
+        let mut instances = state.0.lock().await;
+
+        match result {
+            Ok(subscription) => {
+                let live_query = instances
+                    .get_mut(connection_id)
+                    .and_then(|connection| connection.live_queries.get_mut(&live_id));
+
+                // The live query may have been killed while subscribing
+                match live_query {
+                    Some(live_query) => live_query.handle = subscription,
+                    None => subscription.abort(),
+                }
+            }
+            Err(err) => {
+                println!("Restoring live query {} failed: {}", live_id, err);
+
+                if let Some(connection) = instances.get_mut(connection_id) {
+                    connection.live_queries.remove(&live_id);
+                }
+
+                let payload = LiveError {
+                    connection_id: connection_id.to_owned(),
+                    live_id,
+                    message: err.to_string(),
+                };
+
+                emit_live_error(window, payload);
+            }
+        }
+    }
+}
+
+///
+/// Reconnect using the stored connection info, retrying with exponential
+/// backoff until the connection is restored or closed by the user
+///
+async fn reconnect(window: &tauri::Window, handle: &AppHandle, connection_id: &str) -> bool {
+    let mut backoff = INITIAL_BACKOFF;
+
+    loop {
+        sleep(backoff).await;
+
+        let info = match current_info(handle, connection_id).await {
+            Some(info) => info,
+            None => return false,
+        };
+
+        match connect(&info).await {
+            Ok(client) => {
+                let state = handle.state::<ConnectionState>();
+                let live_queries = match state.0.lock().await.get_mut(connection_id) {
+                    Some(connection) => {
+                        connection.client = client.clone();
+
+                        // Subscriptions on the previous client ended with it
+                        connection
+                            .live_queries
+                            .iter()
+                            .map(|(id, live_query)| {
+                                live_query.handle.abort();
+                                (id.clone(), live_query.table.clone())
+                            })
+                            .collect::<Vec<(String, String)>>()
+                    }
+                    None => return false,
+                };
+
+                restore_live_queries(window, handle, connection_id, &client, live_queries).await;
+
+                return true;
+            }
+            Err(err) => {
+                println!("Reconnecting {} failed: {}", connection_id, err);
+
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        }
+    }
+}
+
+fn emit_status(window: &tauri::Window, event: &str, connection_id: &str) {
+    if let Err(err) = window.emit(event, connection_id) {
+        println!("Failed to deliver {} event: {}", event, err);
+    }
+}
+
+///
+/// Spawn a background task which periodically pings the connection
+/// and restores it when the server can no longer be reached
+///
+pub fn spawn_health_monitor(window: tauri::Window, connection_id: String) -> AbortHandle {
+    let task = tokio::spawn(async move {
+        let handle = window.app_handle();
+
+        loop {
+            sleep(PING_INTERVAL).await;
+
+            let client = match current_client(&handle, &connection_id).await {
+                Some(client) => client,
+                None => break,
+            };
+
+            if client.health().await.is_ok() {
+                continue;
+            }
+
+            println!("Connection {} lost", connection_id);
+            emit_status(&window, "connection:lost", &connection_id);
+
+            if !reconnect(&window, &handle, &connection_id).await {
+                break;
+            }
+
+            println!("Connection {} restored", connection_id);
+            emit_status(&window, "connection:restored", &connection_id);
+        }
+    });
+
+    task.abort_handle()
+}
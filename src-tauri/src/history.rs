@@ -0,0 +1,135 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{config::get_history_path, error::Error};
+
+pub struct HistoryState(pub Mutex<()>);
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: String,
+    pub query: String,
+    pub connection: String,
+    pub endpoint: String,
+    pub namespace: String,
+    pub database: String,
+    pub timestamp: u64,
+    pub duration_ns: u64,
+    pub status: String,
+    pub statements: usize,
+    pub result_size: usize,
+}
+
+pub fn current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+fn read_entries() -> Result<Vec<HistoryEntry>, Error> {
+    let file = match File::open(get_history_path()) {
+        Ok(file) => file,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    let entries = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect();
+
+    Ok(entries)
+}
+
+///
+/// Append an executed query to the history file
+///
+pub fn record_entry(state: &HistoryState, entry: &HistoryEntry) -> Result<(), Error> {
+    let _lock = state.0.lock();
+    let path = get_history_path();
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let line = serde_json::to_string(entry).map_err(|err| Error::Invalid(err.to_string()))?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    writeln!(file, "{}", line)?;
+
+    Ok(())
+}
+
+///
+/// Search the history for queries matching all given filters,
+/// returning the most recent entries first
+///
+#[tauri::command]
+pub fn search_history(
+    state: tauri::State<HistoryState>,
+    text: Option<String>,
+    since: Option<u64>,
+    until: Option<u64>,
+    status: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<HistoryEntry>, Error> {
+    let _lock = state.0.lock();
+    let text = text.map(|t| t.to_lowercase());
+
+    let entries = read_entries()?
+        .into_iter()
+        .rev()
+        .filter(|e| {
+            text.as_ref()
+                .map_or(true, |t| e.query.to_lowercase().contains(t))
+        })
+        .filter(|e| since.map_or(true, |since| e.timestamp >= since))
+        .filter(|e| until.map_or(true, |until| e.timestamp <= until))
+        .filter(|e| status.as_ref().map_or(true, |s| &e.status == s))
+        .take(limit.unwrap_or(usize::MAX))
+        .collect();
+
+    Ok(entries)
+}
+
+///
+/// Remove history entries older than the given timestamp and
+/// limit the history to the given amount of most recent entries
+///
+#[tauri::command]
+pub fn prune_history(
+    state: tauri::State<HistoryState>,
+    before: Option<u64>,
+    keep: Option<usize>,
+) -> Result<usize, Error> {
+    let _lock = state.0.lock();
+    let entries = read_entries()?;
+    let total = entries.len();
+
+    let mut retained = entries
+        .into_iter()
+        .filter(|e| before.map_or(true, |before| e.timestamp >= before))
+        .collect::<Vec<HistoryEntry>>();
+
+    if let Some(keep) = keep {
+        let excess = retained.len().saturating_sub(keep);
+
+        retained.drain(..excess);
+    }
+
+    let mut file = File::create(get_history_path())?;
+
+    for entry in &retained {
+        let line = serde_json::to_string(entry).map_err(|err| Error::Invalid(err.to_string()))?;
+
+        writeln!(file, "{}", line)?;
+    }
+
+    Ok(total - retained.len())
+}
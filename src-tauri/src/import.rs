@@ -0,0 +1,380 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+};
+
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::{Array, Datetime, Number, Object, Table, Value};
+
+use crate::{error::Error, query::ConnectionState, schema::extract_field_definition};
+
+const DEFAULT_BATCH_SIZE: usize = 500;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    Csv,
+    Json,
+    Ndjson,
+}
+
+#[derive(Clone, Serialize)]
+pub struct RowError {
+    pub row: usize,
+    pub message: String,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ImportProgress {
+    pub import_id: String,
+    pub total: usize,
+    pub processed: usize,
+    pub inserted: usize,
+    pub failed: usize,
+    pub errors: Vec<RowError>,
+}
+
+///
+/// Parse CSV text into rows of fields, supporting quoted
+/// fields containing separators, escaped quotes and newlines
+///
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => row.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            (c, _) => field.push(c),
+        }
+    }
+
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows.retain(|row| !(row.len() == 1 && row[0].is_empty()));
+    rows
+}
+
+///
+/// Infer the type of a CSV field without a schema type, falling back
+/// to a string. Floats which are not finite are kept as strings.
+///
+fn infer_value(field: String) -> Value {
+    if field.is_empty() {
+        return Value::None;
+    }
+
+    if let Ok(boolean) = field.parse::<bool>() {
+        return Value::from(boolean);
+    }
+
+    if let Ok(int) = field.parse::<i64>() {
+        return Value::from(int);
+    }
+
+    match field.parse::<f64>() {
+        Ok(float) if float.is_finite() => Value::from(float),
+        _ => Value::from(field),
+    }
+}
+
+fn json_to_value(json: serde_json::Value) -> Value {
+    match json {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(boolean) => Value::from(boolean),
+        serde_json::Value::Number(number) => match number.as_i64() {
+            Some(int) => Value::from(int),
+            None => Value::from(number.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(text) => Value::from(text),
+        serde_json::Value::Array(items) => Value::Array(Array::from(
+            items.into_iter().map(json_to_value).collect::<Vec<Value>>(),
+        )),
+        serde_json::Value::Object(map) => Value::Object(Object::from(
+            map.into_iter()
+                .map(|(key, value)| (key, json_to_value(value)))
+                .collect::<BTreeMap<String, Value>>(),
+        )),
+    }
+}
+
+fn read_records(path: &str, format: &ImportFormat) -> Result<Vec<Vec<(String, Value)>>, Error> {
+    let text = fs::read_to_string(path)?;
+    let invalid = |err: serde_json::Error| Error::Invalid(format!("Invalid JSON: {}", err));
+
+    let objects = match format {
+        ImportFormat::Csv => {
+            let mut rows = parse_csv(&text).into_iter();
+            let header = rows.next().unwrap_or_default();
+
+            return Ok(rows
+                .map(|row| {
+                    header
+                        .iter()
+                        .cloned()
+                        .zip(row.into_iter().map(|field| {
+                            if field.is_empty() {
+                                Value::None
+                            } else {
+                                Value::from(field)
+                            }
+                        }))
+                        .collect()
+                })
+                .collect());
+        }
+        ImportFormat::Json => match serde_json::from_str(&text).map_err(invalid)? {
+            serde_json::Value::Array(items) => items,
+            other => vec![other],
+        },
+        ImportFormat::Ndjson => text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<serde_json::Value>, serde_json::Error>>()
+            .map_err(invalid)?,
+    };
+
+    Ok(objects
+        .into_iter()
+        .map(|object| match object {
+            serde_json::Value::Object(map) => map
+                .into_iter()
+                .map(|(key, value)| (key, json_to_value(value)))
+                .collect(),
+            _ => Vec::new(),
+        })
+        .collect())
+}
+
+///
+/// Convert a value to the kind of the field it is imported into,
+/// as reported by the schema of the target table
+///
+fn convert_value(value: Value, kind: &str) -> Result<Value, String> {
+    let kind = kind
+        .strip_prefix("option<")
+        .and_then(|k| k.strip_suffix('>'))
+        .unwrap_or(kind);
+
+    let text = match &value {
+        Value::Strand(strand) => strand.as_str().to_owned(),
+        Value::None | Value::Null => return Ok(value),
+        _ => value.to_string(),
+    };
+
+    let converted = match kind {
+        "int" => text.parse::<i64>().map(Value::from).ok(),
+        "float" | "number" => text
+            .parse::<f64>()
+            .ok()
+            .filter(|float| float.is_finite())
+            .map(Value::from),
+        // Decimals are parsed from the text to keep their precision
+        "decimal" => text
+            .parse()
+            .map(|decimal| Value::Number(Number::Decimal(decimal)))
+            .ok(),
+        "bool" => text.parse::<bool>().map(Value::from).ok(),
+        "datetime" => Datetime::try_from(text.as_str()).map(Value::from).ok(),
+        "string" => Some(Value::from(text)),
+        _ => return Ok(value),
+    };
+
+    converted.ok_or_else(|| format!("Cannot convert {} to {}", value, kind))
+}
+
+///
+/// Retrieve the kinds of all fields defined on the given table
+///
+async fn fetch_field_kinds(
+    state: &ConnectionState,
+    connection_id: &str,
+    table: &str,
+) -> Result<HashMap<String, String>, Error> {
+    let client = state.client(connection_id).await?;
+    let query = format!("INFO FOR TABLE {}", Table::from(table));
+    let info: Value = client.query(query).await?.take(0)?;
+
+    let mut kinds = HashMap::new();
+
+    if let Value::Object(info) = info {
+        if let Some(Value::Object(fields)) = info.get("fields") {
+            for definition in fields.values() {
+                let definition = match definition {
+                    Value::Strand(definition) => definition.as_str(),
+                    _ => continue,
+                };
+
+                if let Ok(field) = extract_field_definition(definition) {
+                    if !field.kind.is_empty() {
+                        kinds.insert(field.name, field.kind);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(kinds)
+}
+
+///
+/// Map and convert a record, returning the object to insert. Fields with a
+/// schema type are converted from their original text, while the type of
+/// other CSV fields is inferred.
+///
+fn build_record(
+    record: Vec<(String, Value)>,
+    mapping: &Option<HashMap<String, String>>,
+    kinds: &HashMap<String, String>,
+    infer: bool,
+) -> Result<Value, String> {
+    let mut object = Object::default();
+
+    for (key, value) in record {
+        let field = match mapping {
+            Some(mapping) => match mapping.get(&key) {
+                Some(field) => field.to_owned(),
+                None => continue,
+            },
+            None => key,
+        };
+
+        if value.is_none() {
+            continue;
+        }
+
+        let value = match (kinds.get(&field), value) {
+            (Some(kind), value) => convert_value(value, kind)?,
+            (None, Value::Strand(text)) if infer => infer_value(text.0),
+            (None, value) => value,
+        };
+
+        object.insert(field, value);
+    }
+
+    Ok(Value::Object(object))
+}
+
+///
+/// Import records from a local CSV, JSON or NDJSON file into a table. Records
+/// are inserted in batches, each inside a transaction. When a batch fails its
+/// records are inserted individually to report the rows which caused it.
+///
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn import_data(
+    window: tauri::Window,
+    connection_id: String,
+    import_id: String,
+    path: String,
+    format: ImportFormat,
+    table: String,
+    mapping: Option<HashMap<String, String>>,
+    batch_size: Option<usize>,
+    state: tauri::State<'_, ConnectionState>,
+) -> Result<ImportProgress, Error> {
+    state.check_writable(&connection_id).await?;
+
+    println!("Importing {} into {}", path, table);
+
+    let records = read_records(&path, &format)?;
+    let infer = matches!(format, ImportFormat::Csv);
+    let kinds = fetch_field_kinds(&state, &connection_id, &table).await?;
+    let client = state.client(&connection_id).await?;
+    let batch_size = batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+    let target = Table::from(table.as_str()).to_string();
+
+    let mut progress = ImportProgress {
+        import_id,
+        total: records.len(),
+        processed: 0,
+        inserted: 0,
+        failed: 0,
+        errors: Vec::new(),
+    };
+
+    let mut all_errors = Vec::new();
+    let mut rows = records.into_iter().enumerate().peekable();
+
+    while rows.peek().is_some() {
+        let mut batch = Vec::new();
+        let mut errors = Vec::new();
+        let mut taken = 0;
+
+        for (row, record) in rows.by_ref().take(batch_size) {
+            taken += 1;
+
+            match build_record(record, &mapping, &kinds, infer) {
+                Ok(record) => batch.push((row + 1, record)),
+                Err(message) => errors.push(RowError {
+                    row: row + 1,
+                    message,
+                }),
+            }
+        }
+
+        let query = format!(
+            "BEGIN TRANSACTION; INSERT INTO {} $records; COMMIT TRANSACTION;",
+            target
+        );
+
+        let records = batch.iter().map(|(_, r)| r.clone()).collect::<Vec<Value>>();
+        let result = client
+            .query(query)
+            .bind(("records", Value::Array(Array::from(records))))
+            .await
+            .and_then(|response| response.check());
+
+        match result {
+            Ok(_) => progress.inserted += batch.len(),
+            Err(_) => {
+                for (row, record) in &batch {
+                    let query = format!("INSERT INTO {} $record", target);
+                    let result = client
+                        .query(query)
+                        .bind(("record", record.clone()))
+                        .await
+                        .and_then(|response| response.check());
+
+                    match result {
+                        Ok(_) => progress.inserted += 1,
+                        Err(err) => errors.push(RowError {
+                            row: *row,
+                            message: err.to_string(),
+                        }),
+                    }
+                }
+            }
+        }
+
+        progress.processed += taken;
+        progress.failed += errors.len();
+        progress.errors = errors;
+
+        window.emit("import:progress", progress.clone())?;
+
+        all_errors.append(&mut progress.errors);
+    }
+
+    progress.errors = all_errors;
+
+    Ok(progress)
+}
@@ -0,0 +1,106 @@
+#[derive(Clone, Copy, PartialEq)]
+pub enum TokenKind {
+    Word,
+    Text,
+    LineComment,
+    BlockComment,
+    Space,
+    Symbol,
+}
+
+///
+/// A lexical token of a query, referencing its text in the query
+///
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub start: usize,
+    pub text: &'a str,
+}
+
+impl Token<'_> {
+    pub fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+
+    pub fn is_symbol(&self, symbols: &str) -> bool {
+        self.kind == TokenKind::Symbol && symbols.contains(self.text)
+    }
+
+    ///
+    /// Returns whether the token has no effect on the meaning of the query
+    ///
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self.kind,
+            TokenKind::Space | TokenKind::LineComment | TokenKind::BlockComment
+        )
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+///
+/// Split a query into words, strings, comments, whitespace and symbols.
+/// Strings include quoted identifiers and record ids delimited by
+/// backticks or angle brackets.
+///
+pub fn tokenize(query: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = query.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let next = chars.peek().map(|(_, n)| *n);
+
+        let kind = match c {
+            c if c.is_whitespace() => {
+                while chars.next_if(|(_, n)| n.is_whitespace()).is_some() {}
+                TokenKind::Space
+            }
+            '#' | '-' | '/' if c == '#' || next == Some(c) => {
+                while chars.next_if(|(_, n)| *n != '\n').is_some() {}
+                TokenKind::LineComment
+            }
+            '/' if next == Some('*') => {
+                chars.next();
+
+                while let Some((_, n)) = chars.next() {
+                    if n == '*' && chars.next_if(|(_, n)| *n == '/').is_some() {
+                        break;
+                    }
+                }
+
+                TokenKind::BlockComment
+            }
+            '"' | '\'' | '`' | '⟨' => {
+                let close = if c == '⟨' { '⟩' } else { c };
+
+                while let Some((_, n)) = chars.next() {
+                    if n == '\\' {
+                        chars.next();
+                    } else if n == close {
+                        break;
+                    }
+                }
+
+                TokenKind::Text
+            }
+            c if is_word(c) => {
+                while chars.next_if(|(_, n)| is_word(*n)).is_some() {}
+                TokenKind::Word
+            }
+            _ => TokenKind::Symbol,
+        };
+
+        let end = chars.peek().map_or(query.len(), |(i, _)| *i);
+
+        tokens.push(Token {
+            kind,
+            start,
+            text: &query[start..end],
+        });
+    }
+
+    tokens
+}
@@ -0,0 +1,190 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use surrealdb::sql::{
+    parse, statements::DefineStatement, Field, Permission, Statement, Value, Values,
+};
+
+use crate::{error::Error, schema::find_statement_ranges};
+
+#[derive(Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel {
+    Off,
+    #[default]
+    Warning,
+    Error,
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintRule {
+    UnfilteredMutation,
+    UnboundedSelect,
+    SchemalessFields,
+    FullPermissions,
+    UnusedParam,
+}
+
+///
+/// The level of every lint rule, allowing projects
+/// to disable rules or to treat them as errors
+///
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct LintRules {
+    pub unfiltered_mutation: RuleLevel,
+    pub unbounded_select: RuleLevel,
+    pub schemaless_fields: RuleLevel,
+    pub full_permissions: RuleLevel,
+    pub unused_param: RuleLevel,
+}
+
+impl LintRules {
+    fn level(&self, rule: LintRule) -> RuleLevel {
+        match rule {
+            LintRule::UnfilteredMutation => self.unfiltered_mutation,
+            LintRule::UnboundedSelect => self.unbounded_select,
+            LintRule::SchemalessFields => self.schemaless_fields,
+            LintRule::FullPermissions => self.full_permissions,
+            LintRule::UnusedParam => self.unused_param,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct LintIssue {
+    pub rule: LintRule,
+    pub level: RuleLevel,
+    pub message: String,
+    pub statement: usize,
+    pub start: Option<usize>,
+    pub end: Option<usize>,
+}
+
+fn targets_table(what: &Values) -> bool {
+    what.0.iter().any(|value| matches!(value, Value::Table(_)))
+}
+
+fn references_param(text: &str, name: &str) -> bool {
+    let param = format!("${}", name);
+
+    text.match_indices(&param).any(|(i, _)| {
+        !text[i + param.len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_')
+    })
+}
+
+///
+/// Check the statements of a query against the configured rules, reporting
+/// the index and, where available, the byte range of offending statements
+///
+#[tauri::command(async)]
+pub fn lint_query(query: &str, rules: Option<LintRules>) -> Result<Vec<LintIssue>, Error> {
+    let rules = rules.unwrap_or_default();
+    let statements = parse(query)?.0 .0;
+    let ranges = find_statement_ranges(query);
+
+    let mut found = Vec::new();
+    let mut schemaless = HashSet::new();
+    let mut fields = Vec::new();
+
+    for (index, statement) in statements.iter().enumerate() {
+        match statement {
+            Statement::Delete(s) if s.cond.is_none() && targets_table(&s.what) => {
+                let message = format!("DELETE on {} has no WHERE clause", s.what);
+
+                found.push((index, LintRule::UnfilteredMutation, message));
+            }
+            Statement::Update(s) if s.cond.is_none() && targets_table(&s.what) => {
+                let message = format!("UPDATE on {} has no WHERE clause", s.what);
+
+                found.push((index, LintRule::UnfilteredMutation, message));
+            }
+            Statement::Select(s)
+                if s.limit.is_none()
+                    && targets_table(&s.what)
+                    && s.expr.0.iter().any(|f| matches!(f, Field::All)) =>
+            {
+                let message = format!("SELECT * on {} has no LIMIT clause", s.what);
+
+                found.push((index, LintRule::UnboundedSelect, message));
+            }
+            Statement::Define(DefineStatement::Table(t)) => {
+                if !t.full {
+                    schemaless.insert(t.name.to_raw());
+                }
+
+                let perms = &t.permissions;
+                let full = [
+                    ("select", &perms.select),
+                    ("create", &perms.create),
+                    ("update", &perms.update),
+                    ("delete", &perms.delete),
+                ]
+                .into_iter()
+                .filter(|(_, perm)| matches!(perm, Permission::Full))
+                .map(|(action, _)| action)
+                .collect::<Vec<&str>>();
+
+                if t.view.is_none() && !full.is_empty() {
+                    let message = format!(
+                        "Table {} grants scope users full {} access",
+                        t.name,
+                        full.join(", ")
+                    );
+
+                    found.push((index, LintRule::FullPermissions, message));
+                }
+            }
+            Statement::Define(DefineStatement::Field(f)) => {
+                fields.push((index, f.what.to_raw(), f.name.to_string()));
+            }
+            Statement::Set(s) => {
+                let used = statements[index + 1..]
+                    .iter()
+                    .any(|next| references_param(&next.to_string(), &s.name));
+
+                if !used {
+                    let message = format!("Parameter ${} is never used", s.name);
+
+                    found.push((index, LintRule::UnusedParam, message));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (index, table, field) in fields {
+        if schemaless.contains(&table) {
+            let message = format!(
+                "Field {} is defined on schemaless table {}, which does not restrict other fields",
+                field, table
+            );
+
+            found.push((index, LintRule::SchemalessFields, message));
+        }
+    }
+
+    found.sort_by_key(|(index, _, _)| *index);
+
+    // Spans can only be matched to statements when the scanner and
+    // the parser agree on the number of statements in the query
+    let spans = ranges.len() == statements.len();
+
+    Ok(found
+        .into_iter()
+        .filter_map(|(statement, rule, message)| {
+            let level = rules.level(rule);
+            let range = ranges.get(statement).filter(|_| spans);
+
+            (level != RuleLevel::Off).then(|| LintIssue {
+                rule,
+                level,
+                message,
+                statement,
+                start: range.map(|(start, _)| *start),
+                end: range.map(|(_, end)| *end),
+            })
+        })
+        .collect())
+}
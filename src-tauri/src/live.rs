@@ -0,0 +1,156 @@
+use futures::StreamExt;
+use serde::Serialize;
+use surrealdb::{engine::any::Any, sql::Value, Action, Surreal};
+use tokio::task::AbortHandle;
+
+use crate::{error::Error, query::ConnectionState};
+
+pub struct LiveQuery {
+    pub table: String,
+    pub handle: AbortHandle,
+}
+
+#[derive(Clone, Serialize)]
+pub struct LiveNotification {
+    pub connection_id: String,
+    pub live_id: String,
+    pub action: String,
+    pub id: String,
+    pub result: serde_json::Value,
+}
+
+#[derive(Clone, Serialize)]
+pub struct LiveError {
+    pub connection_id: String,
+    pub live_id: String,
+    pub message: String,
+}
+
+fn parse_action(action: &Action) -> String {
+    match action {
+        Action::Create => "CREATE",
+        Action::Update => "UPDATE",
+        Action::Delete => "DELETE",
+        #[allow(unreachable_patterns)]
+        _ => "UNKNOWN",
+    }
+    .to_owned()
+}
+
+fn parse_record_id(data: &Value) -> String {
+    match data {
+        Value::Object(obj) => obj.get("id").map_or("".to_owned(), |id| id.to_string()),
+        _ => "".to_owned(),
+    }
+}
+
+pub fn emit_live_error(window: &tauri::Window, payload: LiveError) {
+    if let Err(err) = window.emit("live:error", payload) {
+        println!("Failed to deliver live query event: {}", err);
+    }
+}
+
+///
+/// Subscribe to changes on a table and spawn a task which
+/// forwards the resulting notifications to the window
+///
+pub async fn subscribe(
+    window: tauri::Window,
+    client: &Surreal<Any>,
+    connection_id: &str,
+    live_id: &str,
+    table: &str,
+) -> Result<AbortHandle, Error> {
+    let mut stream = client.select::<Vec<Value>>(table).live().await?;
+
+    let connection_id = connection_id.to_owned();
+    let live_id = live_id.to_owned();
+    let task = tokio::spawn(async move {
+        while let Some(notification) = stream.next().await {
+            match notification {
+                Ok(notification) => {
+                    let payload = LiveNotification {
+                        connection_id: connection_id.clone(),
+                        live_id: live_id.clone(),
+                        action: parse_action(&notification.action),
+                        id: parse_record_id(&notification.data),
+                        result: notification.data.into_json(),
+                    };
+
+                    if let Err(err) = window.emit("live:notification", payload) {
+                        println!("Failed to deliver live query event: {}", err);
+                    }
+                }
+                Err(err) => {
+                    println!("Live query {} failed: {}", live_id, err);
+
+                    let payload = LiveError {
+                        connection_id: connection_id.clone(),
+                        live_id: live_id.clone(),
+                        message: err.to_string(),
+                    };
+
+                    emit_live_error(&window, payload);
+                }
+            }
+        }
+
+        println!("Live query {} ended", live_id);
+    });
+
+    Ok(task.abort_handle())
+}
+
+#[tauri::command]
+pub async fn start_live_query(
+    window: tauri::Window,
+    connection_id: String,
+    live_id: String,
+    table: String,
+    state: tauri::State<'_, ConnectionState>,
+) -> Result<(), Error> {
+    let client = state.client(&connection_id).await?;
+
+    println!("Starting live query {} on {}", live_id, table);
+
+    let handle = subscribe(window, &client, &connection_id, &live_id, &table).await?;
+    let mut instances = state.0.lock().await;
+
+    // The connection may have been closed while subscribing
+    match instances.get_mut(&connection_id) {
+        Some(connection) => {
+            connection
+                .live_queries
+                .insert(live_id, LiveQuery { table, handle });
+
+            Ok(())
+        }
+        None => {
+            handle.abort();
+
+            Err(Error::NotConnected(connection_id))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn kill_live_query(
+    connection_id: String,
+    live_id: String,
+    state: tauri::State<'_, ConnectionState>,
+) -> Result<bool, Error> {
+    let mut instances = state.0.lock().await;
+    let live_query = instances
+        .get_mut(&connection_id)
+        .and_then(|connection| connection.live_queries.remove(&live_id));
+
+    match live_query {
+        None => Ok(false),
+        Some(LiveQuery { handle, .. }) => {
+            println!("Killing live query {}", live_id);
+            handle.abort();
+
+            Ok(true)
+        }
+    }
+}
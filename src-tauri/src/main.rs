@@ -4,18 +4,37 @@
 )]
 
 use database::DatabaseState;
-use query::ConnectionState;
+use history::HistoryState;
+use query::{ConnectionState, QueryState};
+use results::ResultState;
 use tauri::{Manager, RunEvent};
 
+mod ast;
+mod changes;
 mod config;
 mod database;
+mod dump;
+mod error;
+mod explain;
+mod export;
+mod format;
+mod health;
+mod history;
+mod import;
+mod lexer;
+mod lint;
+mod live;
 mod query;
+mod results;
 mod schema;
 
 fn main() {
     tauri::Builder::default()
         .manage(DatabaseState(Default::default()))
         .manage(ConnectionState(Default::default()))
+        .manage(QueryState(Default::default()))
+        .manage(ResultState(Default::default()))
+        .manage(HistoryState(Default::default()))
         .invoke_handler(tauri::generate_handler![
             config::load_config,
             config::save_config,
@@ -28,21 +47,40 @@ fn main() {
             schema::extract_user_definition,
             schema::validate_query,
             schema::validate_where_clause,
+            schema::split_statements,
+            format::format_query,
+            lint::lint_query,
             database::start_database,
             database::stop_database,
             query::open_connection,
             query::close_connection,
+            query::scope_signup,
             query::execute_query,
+            query::cancel_query,
+            explain::explain_query,
+            results::fetch_results,
+            results::release_results,
+            history::search_history,
+            history::prune_history,
+            live::start_live_query,
+            live::kill_live_query,
+            changes::fetch_changes,
+            export::export_results,
+            import::import_data,
+            dump::export_database,
+            dump::import_database,
         ])
         .build(tauri::generate_context!())
         .expect("tauri should start successfully")
         .run(move |app, event| {
             if let RunEvent::Exit = event {
                 let state = app.state::<DatabaseState>();
-                let process = state.0.lock().unwrap().take();
+                let process = state.0.lock().ok().and_then(|mut process| process.take());
 
                 if let Some(child) = process {
-                    database::kill_surreal_process(child.id())
+                    if let Err(err) = database::kill_surreal_process(child.id()) {
+                        println!("Failed to stop database: {}", err);
+                    }
                 }
             }
         })
@@ -1,63 +1,247 @@
-use std::{collections::HashMap, time::Duration, future::IntoFuture};
+use std::{
+    collections::HashMap,
+    future::IntoFuture,
+    time::{Duration, Instant},
+};
 
 use surrealdb::{
-    engine::remote::ws::{Client, Ws, Wss},
+    engine::any::{self, Any},
     opt::auth::{Database, Namespace, Root, Scope},
-    sql::{Array, Object, Value},
+    sql::{parse, statements::DefineStatement, Array, Block, Function, Object, Statement, Value},
     Surreal,
 };
 
 use serde::Deserialize;
 use tauri::{async_runtime::Mutex, regex::Regex};
-use tokio::time::timeout;
+use tokio::{task::AbortHandle, time::timeout};
+
+use crate::{
+    ast::{block_statements, statement_values, subquery_statement, value_children},
+    error::Error,
+    health::spawn_health_monitor,
+    history::{current_timestamp, record_entry, HistoryEntry, HistoryState},
+    live::LiveQuery,
+    results::{limit_result, CachedResult, ResultState},
+    schema::statement_kind,
+};
 
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 pub struct ScopeField {
     pub subject: String,
-    pub value: String,
+    pub value: serde_json::Value,
 }
 
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 pub struct ConnectionInfo {
     pub namespace: String,
     pub database: String,
     pub endpoint: String,
+    #[serde(default)]
+    pub protocol: String,
     pub username: String,
     pub password: String,
     pub auth_mode: String,
+    #[serde(default)]
+    pub token: String,
     pub scope: String,
     pub scope_fields: Vec<ScopeField>,
+    #[serde(default)]
+    pub read_only: bool,
 }
 
-pub struct ConnectionState(pub Mutex<Option<Surreal<Client>>>);
+pub struct Connection {
+    pub client: Surreal<Any>,
+    pub info: ConnectionInfo,
+    pub live_queries: HashMap<String, LiveQuery>,
+    pub health_monitor: Option<AbortHandle>,
+}
 
-#[tauri::command]
-pub async fn open_connection(
-    info: ConnectionInfo,
-    state: tauri::State<'_, ConnectionState>,
-) -> Result<(), surrealdb::Error> {
-    let regex = Regex::new(r"^(https?://)?(.+?)$").unwrap();
-    let matches = regex.captures(&info.endpoint).unwrap();
-    let endpoint = matches.get(2).unwrap().as_str();
-    let is_secure = info.endpoint.starts_with("https");
+impl Connection {
+    pub fn new(client: Surreal<Any>, info: ConnectionInfo) -> Self {
+        Self {
+            client,
+            info,
+            live_queries: HashMap::new(),
+            health_monitor: None,
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        for (_, live_query) in self.live_queries.drain() {
+            live_query.handle.abort();
+        }
+
+        if let Some(handle) = self.health_monitor.take() {
+            handle.abort();
+        }
+    }
+}
+
+pub struct ConnectionState(pub Mutex<HashMap<String, Connection>>);
+
+impl ConnectionState {
+    ///
+    /// Retrieve the client of the connection with the given id
+    ///
+    pub async fn client(&self, connection_id: &str) -> Result<Surreal<Any>, Error> {
+        let instances = self.0.lock().await;
+
+        instances
+            .get(connection_id)
+            .map(|connection| connection.client.clone())
+            .ok_or_else(|| Error::NotConnected(connection_id.to_owned()))
+    }
+
+    ///
+    /// Returns whether the given connection only permits reading statements
+    ///
+    pub async fn is_read_only(&self, connection_id: &str) -> Result<bool, Error> {
+        let instances = self.0.lock().await;
+
+        instances
+            .get(connection_id)
+            .map(|connection| connection.info.read_only)
+            .ok_or_else(|| Error::NotConnected(connection_id.to_owned()))
+    }
+
+    ///
+    /// Refuse to write data through a read-only connection
+    ///
+    pub async fn check_writable(&self, connection_id: &str) -> Result<(), Error> {
+        if self.is_read_only(connection_id).await? {
+            return Err(Error::Invalid(format!(
+                "Connection {} is read-only",
+                connection_id
+            )));
+        }
+
+        Ok(())
+    }
+
+    ///
+    /// Refuse to run a query containing mutating statements, including
+    /// those nested in subqueries and blocks, on a read-only connection
+    ///
+    pub async fn check_query(&self, connection_id: &str, query: &str) -> Result<(), Error> {
+        if !self.is_read_only(connection_id).await? {
+            return Ok(());
+        }
+
+        let parsed = parse(query).map_err(|err| {
+            Error::Invalid(format!("Query could not be verified as read-only: {}", err))
+        })?;
+
+        match find_mutation(&parsed.0 .0) {
+            Some((index, mutation)) => Err(Error::Invalid(format!(
+                "Statement {} contains {}, which is not allowed on a read-only connection",
+                index + 1,
+                mutation
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+///
+/// Running queries by id, without an abort handle while they are
+/// still being verified and their task has not been spawned yet
+///
+pub struct QueryState(pub Mutex<HashMap<String, Option<AbortHandle>>>);
+
+///
+/// Returns whether the connection uses an engine embedded
+/// in the Surrealist process instead of a remote server
+///
+fn is_embedded(endpoint: &str, protocol: &str) -> bool {
+    match protocol {
+        "mem" | "file" => true,
+        "" => endpoint.starts_with("mem://") || endpoint.starts_with("file://"),
+        _ => false,
+    }
+}
+
+///
+/// Build the address passed to the SDK from the user supplied endpoint. When
+/// no protocol is selected it is derived from the endpoint scheme, where
+/// http and https map to their WebSocket equivalents.
+///
+fn parse_endpoint(endpoint: &str, protocol: &str) -> Result<String, Error> {
+    let endpoint = endpoint.trim();
+
+    if protocol == "mem" || (protocol.is_empty() && endpoint.starts_with("mem://")) {
+        return Ok("mem://".to_owned());
+    }
+
+    if protocol == "file" || (protocol.is_empty() && endpoint.starts_with("file://")) {
+        return Ok(format!("file://{}", endpoint.trim_start_matches("file://")));
+    }
+
+    let regex = Regex::new(r"^(?:([a-zA-Z]+)://)?([^/]+)(/.*)?$").unwrap();
+    let captures = regex
+        .captures(endpoint)
+        .ok_or_else(|| Error::Invalid(format!("Invalid endpoint {}", endpoint)))?;
+
+    let scheme = captures.get(1).map_or("", |m| m.as_str()).to_lowercase();
+    let host = captures.get(2).map_or("", |m| m.as_str());
+    let path = captures.get(3).map_or("", |m| m.as_str());
+
+    let protocol = match (protocol, scheme.as_str()) {
+        ("", "" | "ws" | "http") => "ws",
+        ("", "wss" | "https") => "wss",
+        ("", other) => other,
+        (protocol, _) => protocol,
+    };
+
+    if !matches!(protocol, "ws" | "wss" | "http" | "https") {
+        return Err(Error::Invalid(format!("Unsupported protocol {}", protocol)));
+    }
+
+    // The SDK appends its own rpc or sql path, so strip them from the endpoint
+    // and keep a trailing slash to preserve any prefix used by a proxy
+    let path = path.trim_end_matches('/');
+    let path = path
+        .strip_suffix("/rpc")
+        .or_else(|| path.strip_suffix("/sql"))
+        .unwrap_or(path);
+
+    Ok(format!("{}://{}{}/", protocol, host, path))
+}
+
+fn scope_params(info: &ConnectionInfo) -> HashMap<&str, &serde_json::Value> {
+    info.scope_fields
+        .iter()
+        .map(|field| (field.subject.as_str(), &field.value))
+        .collect()
+}
+
+///
+/// Connect to the endpoint described by the given connection info,
+/// sign in and select the namespace and database
+///
+pub async fn connect(info: &ConnectionInfo) -> Result<Surreal<Any>, Error> {
+    let endpoint = parse_endpoint(&info.endpoint, &info.protocol)?;
 
     println!("Connecting to {}", endpoint);
 
-    let db = if is_secure {
-        Surreal::new::<Wss>(endpoint).await?
+    let db = any::connect(endpoint).await?;
+
+    // Embedded engines run without authentication
+    let auth_mode = if is_embedded(&info.endpoint, &info.protocol) {
+        "none"
     } else {
-        Surreal::new::<Ws>(endpoint).await?
+        info.auth_mode.as_str()
     };
 
-    let mut instance = state.0.lock().await;
-
-    match info.auth_mode.as_str() {
+    match auth_mode {
         "root" => {
             db.signin(Root {
                 username: info.username.as_str(),
                 password: info.password.as_str(),
             })
-            .await?;
+            .await
+            .map_err(Error::auth)?;
         }
         "namespace" => {
             db.signin(Namespace {
@@ -65,7 +249,8 @@ pub async fn open_connection(
                 username: info.username.as_str(),
                 password: info.password.as_str(),
             })
-            .await?;
+            .await
+            .map_err(Error::auth)?;
         }
         "database" => {
             db.signin(Database {
@@ -74,78 +259,480 @@ pub async fn open_connection(
                 username: info.username.as_str(),
                 password: info.password.as_str(),
             })
-            .await?;
+            .await
+            .map_err(Error::auth)?;
         }
         "scope" => {
-            let field_map = info
-                .scope_fields
-                .iter()
-                .map(|field| (field.subject.as_str(), field.value.as_str()))
-                .collect::<HashMap<&str, &str>>();
-
             db.signin(Scope {
                 namespace: info.namespace.as_str(),
                 database: info.database.as_str(),
                 scope: info.scope.as_str(),
-                params: field_map,
+                params: scope_params(info),
             })
-            .await?;
+            .await
+            .map_err(Error::auth)?;
+        }
+        "token" => {
+            db.authenticate(info.token.as_str())
+                .await
+                .map_err(Error::auth)?;
+        }
+        "none" => {}
+        mode => {
+            return Err(Error::Invalid(format!(
+                "Unsupported authentication mode {}",
+                mode
+            )));
         }
-        _ => {}
     };
 
-    db.use_ns(info.namespace).await?;
-    db.use_db(info.database).await?;
+    db.use_ns(info.namespace.as_str()).await?;
+    db.use_db(info.database.as_str()).await?;
+
+    Ok(db)
+}
+
+#[tauri::command]
+pub async fn open_connection(
+    window: tauri::Window,
+    connection_id: String,
+    info: ConnectionInfo,
+    state: tauri::State<'_, ConnectionState>,
+) -> Result<(), Error> {
+    println!("Opening connection {}", connection_id);
+
+    let embedded = is_embedded(&info.endpoint, &info.protocol);
+    let db = connect(&info).await?;
+    let mut connection = Connection::new(db, info);
+
+    // Reconnecting to an embedded engine would replace its data with a new empty
+    // database, so only connections to a remote server are monitored
+    if !embedded {
+        connection.health_monitor = Some(spawn_health_monitor(window, connection_id.clone()));
+    }
 
-    *instance = Some(db);
+    state.0.lock().await.insert(connection_id, connection);
 
     Ok(())
 }
 
 #[tauri::command]
 pub async fn close_connection(
+    connection_id: String,
     state: tauri::State<'_, ConnectionState>,
-) -> Result<(), surrealdb::Error> {
-    let mut instance = state.0.lock().await;
-
-    *instance = None;
+    cache: tauri::State<'_, ResultState>,
+) -> Result<(), Error> {
+    state.0.lock().await.remove(&connection_id);
+    cache.release_connection(&connection_id).await;
 
     Ok(())
 }
 
-fn make_error(err: &str) -> Array {
+///
+/// Sign up to the scope described by the given connection info
+/// and return the issued token
+///
+#[tauri::command]
+pub async fn scope_signup(info: ConnectionInfo) -> Result<String, Error> {
+    let endpoint = parse_endpoint(&info.endpoint, &info.protocol)?;
+
+    println!("Signing up to scope {} on {}", info.scope, endpoint);
+
+    let db = any::connect(endpoint).await?;
+    let token = db
+        .signup(Scope {
+            namespace: info.namespace.as_str(),
+            database: info.database.as_str(),
+            scope: info.scope.as_str(),
+            params: scope_params(&info),
+        })
+        .await
+        .map_err(Error::auth)?;
+
+    Ok(token.as_insecure_token().to_owned())
+}
+
+///
+/// Insert the execution time of the whole batch into a result entry,
+/// using the same human readable format as the SurrealDB CLI
+///
+fn insert_batch_time(entry: &mut Object, elapsed: Duration) {
+    entry.insert(
+        "batch_time".to_owned(),
+        Value::from(format!("{:?}", elapsed)),
+    );
+    entry.insert(
+        "batch_time_ns".to_owned(),
+        Value::from(elapsed.as_nanos() as u64),
+    );
+}
+
+fn make_status(status: &str, message: &str, elapsed: Duration) -> Array {
     let mut results = Array::with_capacity(1);
     let mut entry = Object::default();
 
-    entry.insert("time".to_owned(), Value::from(""));
-    entry.insert("result".to_owned(), Value::from(err));
-    entry.insert("status".to_owned(), Value::from("ERR"));
+    insert_batch_time(&mut entry, elapsed);
+    entry.insert("result".to_owned(), Value::from(message));
+    entry.insert("status".to_owned(), Value::from(status));
 
     results.push(Value::Object(entry));
 
     results
 }
 
+const UNVERIFIED: &str = "an expression which cannot be verified as read-only";
+
+///
+/// Describe the first part of a value which may write data, descending into
+/// subqueries and blocks. Only values known to be safe are allowed, so any
+/// value which cannot be verified is reported as well.
+///
+fn find_nested_mutation(value: &Value) -> Option<String> {
+    match value {
+        Value::Subquery(subquery) => match subquery_statement(subquery) {
+            Some(statement) => find_statement_mutation(&statement),
+            None => Some(UNVERIFIED.to_owned()),
+        },
+        Value::Block(block) => find_block_mutation(block),
+        Value::Future(future) => find_block_mutation(&future.0),
+        // Functions defined on the database may run any statement
+        Value::Function(function) if !matches!(function.as_ref(), Function::Normal(..)) => {
+            Some("a custom function call".to_owned())
+        }
+        _ => match value_children(value) {
+            Some(children) => children
+                .iter()
+                .find_map(|child| find_nested_mutation(child)),
+            None => Some(UNVERIFIED.to_owned()),
+        },
+    }
+}
+
+fn find_block_mutation(block: &Block) -> Option<String> {
+    block_statements(block)
+        .iter()
+        .find_map(|statement| match statement {
+            Some(statement) => find_statement_mutation(statement),
+            None => Some(UNVERIFIED.to_owned()),
+        })
+}
+
+fn find_statement_mutation(statement: &Statement) -> Option<String> {
+    match statement {
+        Statement::Use(_)
+        | Statement::Info(_)
+        | Statement::Begin(_)
+        | Statement::Commit(_)
+        | Statement::Cancel(_)
+        | Statement::Option(_)
+        | Statement::Sleep(_)
+        | Statement::Show(_) => None,
+        Statement::Create(_)
+        | Statement::Update(_)
+        | Statement::Delete(_)
+        | Statement::Relate(_)
+        | Statement::Insert(_)
+        | Statement::Define(_)
+        | Statement::Remove(_)
+        | Statement::Kill(_) => Some(format!("a {} statement", statement_kind(statement))),
+        _ => match statement_values(statement) {
+            Some(values) => values.iter().find_map(|value| find_nested_mutation(value)),
+            None => Some(format!("a {} statement", statement_kind(statement))),
+        },
+    }
+}
+
+///
+/// Find the first statement in the query which is not allowed on a read-only
+/// connection, including statements nested in subqueries and blocks
+///
+fn find_mutation(statements: &[Statement]) -> Option<(usize, String)> {
+    statements
+        .iter()
+        .enumerate()
+        .find_map(|(index, statement)| {
+            find_statement_mutation(statement).map(|mutation| (index, mutation))
+        })
+}
+
+fn make_error(err: &str, elapsed: Duration) -> Array {
+    make_status("ERR", err, elapsed)
+}
+
+fn serialize_results(results: Array) -> String {
+    let result_value = Value::Array(results);
+
+    serde_json::to_string(&result_value.into_json()).unwrap()
+}
+
+const BUILTIN_PARAMS: [&str; 11] = [
+    "auth", "session", "scope", "token", "before", "after", "value", "input", "this", "parent",
+    "event",
+];
+
+///
+/// Collect the parameters referenced in a statement which are not declared
+/// using LET by a preceding statement in the same or an enclosing block
+///
+fn collect_params(statement: &Statement, declared: &mut Vec<String>, params: &mut Vec<String>) {
+    match statement {
+        // Parameters in the bodies of DEFINE statements
+        // are only bound once they are invoked
+        Statement::Define(DefineStatement::Param(param)) => {
+            declared.push(param.name.to_raw());
+            return;
+        }
+        Statement::Define(_) => return,
+        _ => {}
+    }
+
+    for value in statement_values(statement).unwrap_or_default() {
+        collect_value_params(&value, declared, params);
+    }
+
+    if let Statement::Set(set) = statement {
+        declared.push(set.name.clone());
+    }
+}
+
+fn collect_value_params(value: &Value, declared: &[String], params: &mut Vec<String>) {
+    match value {
+        Value::Param(param) => {
+            let name = param.to_raw();
+
+            if !declared.contains(&name) && !params.contains(&name) {
+                params.push(name);
+            }
+        }
+        Value::Subquery(subquery) => {
+            if let Some(statement) = subquery_statement(subquery) {
+                collect_params(&statement, &mut declared.to_vec(), params);
+            }
+        }
+        Value::Block(block) => collect_block_params(block, declared, params),
+        Value::Future(future) => collect_block_params(&future.0, declared, params),
+        _ => {
+            for child in value_children(value).unwrap_or_default() {
+                collect_value_params(&child, declared, params);
+            }
+        }
+    }
+}
+
+fn collect_block_params(block: &Block, declared: &[String], params: &mut Vec<String>) {
+    let mut scope = declared.to_vec();
+
+    for statement in block_statements(block).iter().flatten() {
+        collect_params(statement, &mut scope, params);
+    }
+}
+
+///
+/// Find all parameters referenced by the statements of the query which are
+/// neither bound as variable, declared using LET or DEFINE PARAM, nor
+/// provided by SurrealDB
+///
+fn find_unbound_params(
+    query: &str,
+    variables: &serde_json::Map<String, serde_json::Value>,
+) -> Vec<String> {
+    let statements = match parse(query) {
+        Ok(parsed) => parsed.0 .0,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut declared = Vec::new();
+    let mut params = Vec::new();
+
+    for statement in &statements {
+        collect_params(statement, &mut declared, &mut params);
+    }
+
+    params.retain(|name| !variables.contains_key(name) && !BUILTIN_PARAMS.contains(&name.as_str()));
+    params
+}
+
+///
+/// Retrieve the names of the parameters defined on the selected database,
+/// returning nothing when the session is not allowed to list them
+///
+async fn find_database_params(client: &Surreal<Any>) -> Option<Vec<String>> {
+    let info: Value = client.query("INFO FOR DB").await.ok()?.take(0).ok()?;
+
+    match info {
+        Value::Object(info) => match info.get("params") {
+            Some(Value::Object(params)) => Some(params.keys().cloned().collect()),
+            _ => Some(Vec::new()),
+        },
+        _ => None,
+    }
+}
+
+///
+/// Verify that all parameters of the query are bound and that it is allowed
+/// on the connection, returning the reason when the query is rejected
+///
+async fn verify_query(
+    state: &ConnectionState,
+    client: &Surreal<Any>,
+    connection_id: &str,
+    query: &str,
+    variables: &serde_json::Map<String, serde_json::Value>,
+) -> Result<Option<String>, Error> {
+    let mut unbound = find_unbound_params(query, variables);
+
+    // Parameters defined on the database are only looked up when needed,
+    // and the check is skipped when they cannot be listed
+    if !unbound.is_empty() {
+        match find_database_params(client).await {
+            Some(defined) => unbound.retain(|name| !defined.contains(name)),
+            None => unbound.clear(),
+        }
+    }
+
+    if !unbound.is_empty() {
+        let names = unbound
+            .iter()
+            .map(|name| format!("${}", name))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        return Ok(Some(format!("No value bound for parameter {}", names)));
+    }
+
+    match state.check_query(connection_id, query).await {
+        Err(Error::Invalid(message)) => Ok(Some(message)),
+        result => result.map(|_| None),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 #[tauri::command]
 pub async fn execute_query(
+    connection_id: String,
+    query_id: String,
     query: String,
+    variables: Option<serde_json::Value>,
     max_time: u64,
+    max_bytes: Option<usize>,
+    record_history: Option<bool>,
     state: tauri::State<'_, ConnectionState>,
-) -> Result<String, surrealdb::Error> {
+    queries: tauri::State<'_, QueryState>,
+    cache: tauri::State<'_, ResultState>,
+    history: tauri::State<'_, HistoryState>,
+) -> Result<String, Error> {
     println!("Executing query {}", query);
 
-    let instance = state.0.lock().await;
-    let client = instance.as_ref().unwrap();
+    let timestamp = current_timestamp();
+    let client = state.client(&connection_id).await?;
+
+    // The connection is looked up upfront so that queries which
+    // are rejected before they run are recorded in the history too
+    let history_entry = if record_history.unwrap_or(false) {
+        let instances = state.0.lock().await;
+
+        instances
+            .get(&connection_id)
+            .map(|connection| (query.clone(), connection.info.clone()))
+    } else {
+        None
+    };
+
+    let finish = |results: Array, elapsed: Duration| {
+        let statements = results.len();
+        let status = results
+            .iter()
+            .filter_map(|entry| match entry {
+                Value::Object(entry) => match entry.get("status") {
+                    Some(Value::Strand(status)) => Some(status.as_str().to_owned()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .find(|status| status != "OK")
+            .unwrap_or_else(|| "OK".to_owned());
+
+        let result_json = serialize_results(results);
+
+        if let Some((query, info)) = &history_entry {
+            let entry = HistoryEntry {
+                id: query_id.clone(),
+                query: query.clone(),
+                connection: connection_id.clone(),
+                endpoint: info.endpoint.clone(),
+                namespace: info.namespace.clone(),
+                database: info.database.clone(),
+                timestamp,
+                duration_ns: elapsed.as_nanos() as u64,
+                status,
+                statements,
+                result_size: result_json.len(),
+            };
+
+            if let Err(err) = record_entry(&history, &entry) {
+                println!("Failed to record query history: {}", err);
+            }
+        }
+
+        result_json
+    };
+
+    let variables = match variables {
+        None | Some(serde_json::Value::Null) => serde_json::Map::new(),
+        Some(serde_json::Value::Object(map)) => map,
+        Some(_) => {
+            let message = "Query variables must be an object";
+
+            return Ok(finish(make_error(message, Duration::ZERO), Duration::ZERO));
+        }
+    };
+
+    // The query is registered before it is verified so
+    // that it can be cancelled while the checks are running
+    queries.0.lock().await.insert(query_id.clone(), None);
+
+    let rejection = match verify_query(&state, &client, &connection_id, &query, &variables).await {
+        Ok(rejection) => rejection,
+        Err(err) => {
+            queries.0.lock().await.remove(&query_id);
+            return Err(err);
+        }
+    };
+
+    if let Some(message) = rejection {
+        queries.0.lock().await.remove(&query_id);
+        return Ok(finish(make_error(&message, Duration::ZERO), Duration::ZERO));
+    }
+
+    // Truncated results of previous queries are no longer displayed
+    cache.release_connection(&connection_id).await;
 
-    let query_task = client.query(query);
     let timeout_duration = Duration::from_secs(max_time);
-    let timeout_result = timeout(timeout_duration, query_task.into_future()).await;
+    let started_at = Instant::now();
+    let query_task = tokio::spawn(async move {
+        let query_future = client.query(query).bind(variables).into_future();
+
+        timeout(timeout_duration, query_future).await
+    });
+
+    // The query may have been cancelled while it was being verified
+    match queries.0.lock().await.get_mut(&query_id) {
+        Some(pending) => *pending = Some(query_task.abort_handle()),
+        None => query_task.abort(),
+    }
+
+    let task_result = query_task.await;
+    let elapsed = started_at.elapsed();
+
+    queries.0.lock().await.remove(&query_id);
 
-    println!("Query task completed");
+    println!("Query task completed in {:?}", elapsed);
 
-    let results: Array = match timeout_result {
-        Ok(query_result) => match query_result {
+    let results: Array = match task_result {
+        Ok(Ok(query_result)) => match query_result {
             Ok(mut response) => {
+                // The SDK does not expose the execution time of individual
+                // statements, so only the duration of the whole batch is known
                 let statement_count = response.num_statements();
 
                 let mut results = Array::with_capacity(statement_count);
@@ -155,7 +742,7 @@ pub async fn execute_query(
                     let mut entry = Object::default();
                     let error = errors.get(&i);
 
-                    entry.insert("time".to_owned(), Value::from(""));
+                    insert_batch_time(&mut entry, elapsed);
 
                     let result: Value;
                     let status: Value;
@@ -165,10 +752,38 @@ pub async fn execute_query(
                             result = Value::from(error.to_string());
                             status = "ERR".into();
                         }
-                        None => {
-                            result = response.take(i).unwrap();
-                            status = "OK".into();
-                        }
+                        None => match response.take(i) {
+                            Ok(value) => {
+                                let limited = limit_result(value, max_bytes);
+
+                                entry.insert("size".to_owned(), Value::from(limited.size));
+
+                                entry.insert("count".to_owned(), Value::from(limited.count));
+                                entry.insert(
+                                    "truncated".to_owned(),
+                                    Value::from(limited.items.is_some()),
+                                );
+
+                                if let Some(items) = limited.items {
+                                    let cursor = format!("{}:{}", query_id, i);
+
+                                    entry.insert("cursor".to_owned(), Value::from(cursor.as_str()));
+                                    let cached = CachedResult {
+                                        connection_id: connection_id.clone(),
+                                        items,
+                                    };
+
+                                    cache.0.lock().await.insert(cursor, cached);
+                                }
+
+                                result = limited.value;
+                                status = "OK".into();
+                            }
+                            Err(error) => {
+                                result = Value::from(error.to_string());
+                                status = "ERR".into();
+                            }
+                        },
                     };
 
                     entry.insert("result".to_owned(), result);
@@ -183,19 +798,168 @@ pub async fn execute_query(
                 let message = error.to_string();
 
                 println!("Query resulted in error: {}", message);
-                make_error(&message)
+                make_error(&message, elapsed)
             }
         },
-        Err(_) => {
-            let message = format!("Query timed out after {} seconds", max_time);
+        Ok(Err(_)) => {
+            let message = Error::Timeout(max_time).to_string();
 
             println!("Query resulted in timeout");
-            make_error(&message)
+            make_error(&message, elapsed)
+        }
+        Err(err) if err.is_cancelled() => {
+            println!("Query was cancelled");
+            make_status("CANCELLED", "Query was cancelled", elapsed)
+        }
+        Err(err) => {
+            let message = err.to_string();
+
+            println!("Query task failed: {}", message);
+            make_error(&message, elapsed)
         }
     };
 
-    let result_value = Value::Array(results);
-    let result_json = serde_json::to_string(&result_value.into_json()).unwrap();
+    Ok(finish(results, elapsed))
+}
+
+#[tauri::command]
+pub async fn cancel_query(
+    query_id: String,
+    queries: tauri::State<'_, QueryState>,
+) -> Result<bool, Error> {
+    let query = queries.0.lock().await.remove(&query_id);
+
+    match query {
+        None => Ok(false),
+        Some(handle) => {
+            // Queries which are still being verified are aborted once spawned
+            if let Some(handle) = handle {
+                handle.abort();
+            }
+
+            Ok(true)
+        }
+    }
+}
 
-    Ok(result_json)
+#[cfg(test)]
+mod tests {
+    use tauri::async_runtime::block_on;
+
+    use super::*;
+
+    #[test]
+    fn client_of_unknown_connection_returns_not_connected() {
+        let state = ConnectionState(Default::default());
+        let result = block_on(state.client("missing"));
+
+        assert!(matches!(result, Err(Error::NotConnected(id)) if id == "missing"));
+    }
+
+    #[test]
+    fn endpoint_scheme_selects_the_protocol() {
+        assert_eq!(parse_endpoint("ws://host", "").unwrap(), "ws://host/");
+        assert_eq!(
+            parse_endpoint("localhost:8000", "").unwrap(),
+            "ws://localhost:8000/"
+        );
+        assert_eq!(
+            parse_endpoint("http://host", "https").unwrap(),
+            "https://host/"
+        );
+    }
+
+    #[test]
+    fn endpoint_keeps_proxy_prefix_without_rpc_path() {
+        let endpoint = parse_endpoint("https://host/proxy/rpc", "").unwrap();
+
+        assert_eq!(endpoint, "wss://host/proxy/");
+    }
+
+    #[test]
+    fn endpoint_with_unsupported_scheme_is_invalid() {
+        let result = parse_endpoint("ftp://host", "");
+
+        assert!(matches!(result, Err(Error::Invalid(_))));
+    }
+
+    fn unbound_of(query: &str) -> Vec<String> {
+        find_unbound_params(query, &serde_json::Map::new())
+    }
+
+    #[test]
+    fn escaped_identifiers_are_not_params() {
+        assert!(unbound_of("SELECT * FROM person:⟨$abc⟩").is_empty());
+        assert!(unbound_of("SELECT `$x` FROM person").is_empty());
+    }
+
+    #[test]
+    fn block_declarations_do_not_leak() {
+        assert_eq!(
+            unbound_of("RETURN { LET $x = 1; RETURN $x; }; RETURN $x"),
+            vec!["x"]
+        );
+        assert!(unbound_of("LET $x = 1; RETURN { RETURN $x; }").is_empty());
+    }
+
+    #[test]
+    fn define_bodies_are_skipped() {
+        let query = "DEFINE SCOPE user SIGNIN (SELECT * FROM user WHERE name = $name)";
+
+        assert!(unbound_of(query).is_empty());
+        assert!(
+            unbound_of("DEFINE PARAM $limit VALUE 10; SELECT * FROM t LIMIT $limit").is_empty()
+        );
+    }
+
+    fn mutation_of(query: &str) -> Option<(usize, String)> {
+        find_mutation(&parse(query).unwrap().0 .0)
+    }
+
+    #[test]
+    fn keywords_in_values_are_not_mutations() {
+        assert_eq!(
+            mutation_of("SELECT * FROM person WHERE flags = { update: true }"),
+            None
+        );
+        assert_eq!(mutation_of("SELECT count() FROM person GROUP ALL"), None);
+    }
+
+    #[test]
+    fn nested_mutations_are_found() {
+        assert_eq!(
+            mutation_of("SELECT * FROM (CREATE person)"),
+            Some((0, "a CREATE statement".to_owned()))
+        );
+        assert_eq!(
+            mutation_of("SELECT 1; LET $x = { DELETE person; }"),
+            Some((1, "a DELETE statement".to_owned()))
+        );
+    }
+
+    #[test]
+    fn custom_function_calls_are_mutations() {
+        assert!(mutation_of("RETURN fn::purge_users()").is_some());
+    }
+
+    #[test]
+    fn mutations_in_casts_are_found() {
+        assert!(mutation_of("RETURN <string> (DELETE person)").is_some());
+    }
+
+    #[test]
+    fn mutations_in_idiom_filters_are_found() {
+        assert!(mutation_of("SELECT friends[WHERE (DELETE person)] FROM person").is_some());
+    }
+
+    #[test]
+    fn mutations_in_select_clauses_are_found() {
+        assert!(mutation_of("SELECT * FROM person LIMIT (DELETE person)").is_some());
+        assert!(mutation_of("SELECT * FROM person START (DELETE person)").is_some());
+    }
+
+    #[test]
+    fn mutations_in_record_ids_are_found() {
+        assert!(mutation_of("SELECT * FROM person:[(DELETE person)]").is_some());
+    }
 }
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use surrealdb::sql::{Array, Value};
+use tauri::async_runtime::Mutex;
+
+use crate::error::Error;
+
+pub struct CachedResult {
+    pub connection_id: String,
+    pub items: Vec<serde_json::Value>,
+}
+
+pub struct ResultState(pub Mutex<HashMap<String, CachedResult>>);
+
+impl ResultState {
+    ///
+    /// Release the truncated results of all queries executed on a connection
+    ///
+    pub async fn release_connection(&self, connection_id: &str) {
+        self.0
+            .lock()
+            .await
+            .retain(|_, result| result.connection_id != connection_id);
+    }
+}
+
+pub struct LimitedResult {
+    pub value: Value,
+    pub size: usize,
+    pub count: usize,
+    pub items: Option<Vec<serde_json::Value>>,
+}
+
+#[derive(Serialize)]
+pub struct ResultPage {
+    pub items: Vec<serde_json::Value>,
+    pub offset: usize,
+    pub total: usize,
+}
+
+fn json_size(value: &serde_json::Value) -> usize {
+    serde_json::to_vec(value).map_or(0, |bytes| bytes.len())
+}
+
+///
+/// Measure the serialized size of a statement result and truncate it
+/// when it exceeds the given amount of bytes. The items of truncated
+/// results are returned so they can be paged through with a cursor.
+///
+pub fn limit_result(value: Value, max_bytes: Option<usize>) -> LimitedResult {
+    let count = match &value {
+        Value::Array(array) => array.len(),
+        Value::None | Value::Null => 0,
+        _ => 1,
+    };
+
+    let json = value.clone().into_json();
+    let size = json_size(&json);
+
+    // Results are only truncated when they exceed the limit
+    let max_bytes = match max_bytes {
+        Some(max_bytes) if size > max_bytes => max_bytes,
+        _ => {
+            return LimitedResult {
+                value,
+                size,
+                count,
+                items: None,
+            }
+        }
+    };
+
+    let items = match json {
+        serde_json::Value::Array(items) => items,
+        other => vec![other],
+    };
+
+    let mut kept = 0;
+    let mut kept_size = 0;
+
+    for item in &items {
+        kept_size += json_size(item);
+
+        if kept_size > max_bytes {
+            break;
+        }
+
+        kept += 1;
+    }
+
+    let value = match value {
+        Value::Array(array) => Value::Array(Array::from(
+            array.0.into_iter().take(kept).collect::<Vec<Value>>(),
+        )),
+        _ => Value::None,
+    };
+
+    LimitedResult {
+        value,
+        size,
+        count,
+        items: Some(items),
+    }
+}
+
+#[tauri::command]
+pub async fn fetch_results(
+    cursor: String,
+    offset: usize,
+    limit: usize,
+    state: tauri::State<'_, ResultState>,
+) -> Result<ResultPage, Error> {
+    let cache = state.0.lock().await;
+    let items = &cache
+        .get(&cursor)
+        .ok_or_else(|| Error::Invalid(format!("Unknown result cursor {}", cursor)))?
+        .items;
+
+    Ok(ResultPage {
+        items: items.iter().skip(offset).take(limit).cloned().collect(),
+        offset,
+        total: items.len(),
+    })
+}
+
+#[tauri::command]
+pub async fn release_results(
+    cursor: String,
+    state: tauri::State<'_, ResultState>,
+) -> Result<bool, Error> {
+    Ok(state.0.lock().await.remove(&cursor).is_some())
+}
@@ -1,6 +1,8 @@
 use serde::Serialize;
 use surrealdb::sql::{parse, statements::DefineStatement, Index, Permissions, Statement, Strand};
 
+use crate::{error::Error, lexer::tokenize};
+
 #[derive(Serialize)]
 pub struct PermissionInfo {
     pub select: String,
@@ -35,7 +37,7 @@ pub struct ScopeInfo {
 }
 
 #[tauri::command(async)]
-pub fn extract_scope_definition(definition: &str) -> Result<ScopeInfo, String> {
+pub fn extract_scope_definition(definition: &str) -> Result<ScopeInfo, Error> {
     let parsed = parse(definition)?;
     let query = &parsed[0];
 
@@ -62,7 +64,7 @@ pub fn extract_scope_definition(definition: &str) -> Result<ScopeInfo, String> {
         });
     }
 
-    Err(String::from("Failed to extract scope"))
+    Err(Error::Invalid("Failed to extract scope".to_owned()))
 }
 
 #[derive(Serialize)]
@@ -86,7 +88,7 @@ pub struct TableInfo {
 }
 
 #[tauri::command(async)]
-pub fn extract_table_definition(definition: &str) -> Result<TableInfo, String> {
+pub fn extract_table_definition(definition: &str) -> Result<TableInfo, Error> {
     let parsed = parse(definition)?;
     let query = &parsed[0];
 
@@ -112,7 +114,7 @@ pub fn extract_table_definition(definition: &str) -> Result<TableInfo, String> {
                 .map_or("".to_owned(), |c| c.to_string()),
         });
     }
-    Err(String::from("Failed to extract table"))
+    Err(Error::Invalid("Failed to extract table".to_owned()))
 }
 
 #[derive(Serialize)]
@@ -128,7 +130,7 @@ pub struct FieldInfo {
 }
 
 #[tauri::command(async)]
-pub fn extract_field_definition(definition: &str) -> Result<FieldInfo, String> {
+pub fn extract_field_definition(definition: &str) -> Result<FieldInfo, Error> {
     let parsed = parse(definition)?;
     let query = &parsed[0];
 
@@ -144,7 +146,7 @@ pub fn extract_field_definition(definition: &str) -> Result<FieldInfo, String> {
             comment: parse_comment(&f.comment),
         });
     }
-    Err(String::from("Failed to extract field"))
+    Err(Error::Invalid("Failed to extract field".to_owned()))
 }
 
 #[derive(Serialize)]
@@ -156,7 +158,7 @@ pub struct AnalyzerInfo {
 }
 
 #[tauri::command(async)]
-pub fn extract_analyzer_definition(definition: &str) -> Result<AnalyzerInfo, String> {
+pub fn extract_analyzer_definition(definition: &str) -> Result<AnalyzerInfo, Error> {
     let parsed = parse(definition)?;
     let query = &parsed[0];
 
@@ -181,7 +183,7 @@ pub fn extract_analyzer_definition(definition: &str) -> Result<AnalyzerInfo, Str
         });
     }
 
-    Err(String::from("Failed to extract index"))
+    Err(Error::Invalid("Failed to extract index".to_owned()))
 }
 
 #[derive(Serialize)]
@@ -203,7 +205,7 @@ pub struct IndexInfo {
 }
 
 #[tauri::command(async)]
-pub fn extract_index_definition(definition: &str) -> Result<IndexInfo, String> {
+pub fn extract_index_definition(definition: &str) -> Result<IndexInfo, Error> {
     let parsed = parse(definition)?;
     let query = &parsed[0];
 
@@ -234,7 +236,7 @@ pub fn extract_index_definition(definition: &str) -> Result<IndexInfo, String> {
         });
     }
 
-    Err(String::from("Failed to extract index"))
+    Err(Error::Invalid("Failed to extract index".to_owned()))
 }
 
 #[derive(Serialize)]
@@ -246,7 +248,7 @@ pub struct EventInfo {
 }
 
 #[tauri::command(async)]
-pub fn extract_event_definition(definition: &str) -> Result<EventInfo, String> {
+pub fn extract_event_definition(definition: &str) -> Result<EventInfo, Error> {
     let parsed = parse(definition)?;
     let query = &parsed[0];
 
@@ -261,7 +263,7 @@ pub fn extract_event_definition(definition: &str) -> Result<EventInfo, String> {
         });
     }
 
-    Err(String::from("Failed to extract event"))
+    Err(Error::Invalid("Failed to extract event".to_owned()))
 }
 
 #[derive(Serialize)]
@@ -272,7 +274,7 @@ pub struct UserInfo {
 }
 
 #[tauri::command(async)]
-pub fn extract_user_definition(definition: &str) -> Result<UserInfo, String> {
+pub fn extract_user_definition(definition: &str) -> Result<UserInfo, Error> {
     let parsed = parse(definition)?;
     let query = &parsed[0];
 
@@ -288,22 +290,228 @@ pub fn extract_user_definition(definition: &str) -> Result<UserInfo, String> {
         });
     }
 
-    Err(String::from("Failed to extract user"))
+    Err(Error::Invalid("Failed to extract user".to_owned()))
 }
 
-#[tauri::command(async)]
-pub fn validate_query(query: &str) -> Option<String> {
-    let parsed = parse(query);
+const WHERE_PREFIX: &str = "SELECT * FROM table WHERE ";
+
+const STATEMENT_KEYWORDS: [&str; 16] = [
+    "SELECT", "CREATE", "UPDATE", "DELETE", "RELATE", "INSERT", "DEFINE", "REMOVE", "LET",
+    "RETURN", "INFO", "USE", "BEGIN", "COMMIT", "CANCEL", "LIVE",
+];
+
+const DEFINE_KEYWORDS: [&str; 12] = [
+    "NAMESPACE",
+    "DATABASE",
+    "TABLE",
+    "FIELD",
+    "INDEX",
+    "EVENT",
+    "SCOPE",
+    "PARAM",
+    "FUNCTION",
+    "ANALYZER",
+    "TOKEN",
+    "USER",
+];
+
+#[derive(Serialize)]
+pub struct Diagnostic {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    pub snippet: String,
+    pub expected: Vec<String>,
+}
+
+///
+/// Suggest the tokens which could follow the last keyword before
+/// the error position. This is a best effort hint, as the parser
+/// does not report what it expected.
+///
+fn suggest_expected(query: &str, line: usize, column: usize) -> Vec<String> {
+    let prefix = query
+        .lines()
+        .take(line.max(1))
+        .enumerate()
+        .map(|(i, text)| {
+            if i + 1 == line.max(1) {
+                text.chars().take(column.saturating_sub(1)).collect()
+            } else {
+                text.to_owned()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let prefix = prefix.trim_end();
+    let last_word = prefix
+        .rsplit(|c: char| c.is_whitespace() || c == ';')
+        .next()
+        .unwrap_or_default()
+        .to_uppercase();
+
+    let expected: &[&str] = match last_word.as_str() {
+        _ if prefix.is_empty() || prefix.ends_with(';') => &STATEMENT_KEYWORDS,
+        "DEFINE" | "REMOVE" => &DEFINE_KEYWORDS,
+        "SELECT" => &["*", "VALUE", "<field>"],
+        "FROM" | "CREATE" | "UPDATE" | "DELETE" | "INTO" | "ON" => &["<table>", "<record id>"],
+        "WHERE" | "IF" | "WHEN" => &["<condition>"],
+        "ORDER" | "GROUP" | "SPLIT" => &["BY"],
+        "LET" => &["$<param>"],
+        "SET" => &["<field> = <value>"],
+        "CONTENT" | "MERGE" | "PATCH" => &["<object>"],
+        "=" | "!=" | "==" | ">" | "<" | ">=" | "<=" | "+" | "-" | "*" | "/" => &["<value>"],
+        _ => &[],
+    };
+
+    expected.iter().map(|s| s.to_string()).collect()
+}
 
-    match parsed {
-        Ok(_) => None,
-        Err(err) => Some(err.to_string()),
+fn diagnose(query: &str, err: Error) -> Diagnostic {
+    match err {
+        Error::Parse {
+            message,
+            line,
+            column,
+            snippet,
+        } => Diagnostic {
+            expected: suggest_expected(query, line, column),
+            message,
+            line,
+            column,
+            snippet,
+        },
+        err => Diagnostic {
+            message: err.to_string(),
+            line: 0,
+            column: 0,
+            snippet: String::new(),
+            expected: Vec::new(),
+        },
     }
 }
 
 #[tauri::command(async)]
-pub fn validate_where_clause(clause: &str) -> bool {
-    let query = "SELECT * FROM table WHERE ".to_owned() + clause;
+pub fn validate_query(query: &str) -> Option<Diagnostic> {
+    parse(query).err().map(|err| diagnose(query, err.into()))
+}
+
+#[tauri::command(async)]
+pub fn validate_where_clause(clause: &str) -> Option<Diagnostic> {
+    let query = WHERE_PREFIX.to_owned() + clause;
+    let mut diagnostic = parse(&query)
+        .err()
+        .map(|err| diagnose(&query, err.into()))?;
+
+    // Positions on the first line include the prefix added above
+    if diagnostic.line == 1 {
+        diagnostic.column = diagnostic.column.saturating_sub(WHERE_PREFIX.len()).max(1);
+        diagnostic.message = format!(
+            "Parse error on line {} at character {} when parsing '{}'",
+            diagnostic.line, diagnostic.column, diagnostic.snippet
+        );
+    }
 
-    parse(&query).is_ok()
+    Some(diagnostic)
+}
+
+#[derive(Serialize)]
+pub struct StatementInfo {
+    pub kind: String,
+    pub start: usize,
+    pub end: usize,
+    pub error: Option<String>,
+}
+
+pub fn statement_kind(statement: &Statement) -> &'static str {
+    match statement {
+        Statement::Select(_) => "SELECT",
+        Statement::Create(_) => "CREATE",
+        Statement::Update(_) => "UPDATE",
+        Statement::Delete(_) => "DELETE",
+        Statement::Relate(_) => "RELATE",
+        Statement::Insert(_) => "INSERT",
+        Statement::Define(_) => "DEFINE",
+        Statement::Remove(_) => "REMOVE",
+        Statement::Kill(_) => "KILL",
+        Statement::Live(_) => "LIVE",
+        Statement::Info(_) => "INFO",
+        Statement::Use(_) => "USE",
+        Statement::Set(_) => "LET",
+        Statement::Output(_) => "RETURN",
+        Statement::Ifelse(_) => "IF",
+        Statement::Begin(_) => "BEGIN",
+        Statement::Commit(_) => "COMMIT",
+        Statement::Cancel(_) => "CANCEL",
+        Statement::Option(_) => "OPTION",
+        Statement::Sleep(_) => "SLEEP",
+        Statement::Show(_) => "SHOW",
+        Statement::Value(_) => "VALUE",
+        #[allow(unreachable_patterns)]
+        _ => "OTHER",
+    }
+}
+
+///
+/// Find the byte ranges of all top level statements in the query,
+/// skipping over strings, comments and nested blocks
+///
+pub fn find_statement_ranges(query: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut depth: usize = 0;
+    let mut start: Option<usize> = None;
+    let mut end = 0;
+
+    for token in tokenize(query) {
+        if token.is_trivia() {
+            continue;
+        }
+
+        if token.is_symbol(";") && depth == 0 {
+            if let Some(start) = start.take() {
+                ranges.push((start, end));
+            }
+
+            continue;
+        }
+
+        if token.is_symbol("([{") {
+            depth += 1;
+        } else if token.is_symbol(")]}") {
+            depth = depth.saturating_sub(1);
+        }
+
+        start.get_or_insert(token.start);
+        end = token.end();
+    }
+
+    if let Some(start) = start {
+        ranges.push((start, end));
+    }
+
+    ranges
+}
+
+#[tauri::command(async)]
+pub fn split_statements(query: &str) -> Vec<StatementInfo> {
+    find_statement_ranges(query)
+        .into_iter()
+        .map(|(start, end)| {
+            let (kind, error) = match parse(&query[start..end]) {
+                Ok(parsed) => match parsed.0 .0.first() {
+                    Some(statement) => (statement_kind(statement).to_owned(), None),
+                    None => ("EMPTY".to_owned(), None),
+                },
+                Err(err) => ("INVALID".to_owned(), Some(err.to_string())),
+            };
+
+            StatementInfo {
+                kind,
+                start,
+                end,
+                error,
+            }
+        })
+        .collect()
 }
@@ -6,9 +6,9 @@ import { Stack, Text } from "@mantine/core";
 import { showNotification } from "@mantine/notifications";
 import { actions, store } from "~/store";
 import { SurrealistAdapter } from "./base";
-import { extractTypeList, printLog } from "~/util/helpers";
+import { extractTypeList, newId, printLog } from "~/util/helpers";
 import { map, mapKeys, snake } from "radash";
-import { TableSchema, TableField, TableIndex, TableEvent, SurrealHandle, SurrealOptions, IndexKind } from "~/types";
+import { TableSchema, TableField, TableIndex, TableEvent, SurrealHandle, SurrealOptions, IndexKind, Diagnostic } from "~/types";
 import { SurrealInfoDB, SurrealInfoTB } from "~/typings/surreal";
 
 const WAIT_DURATION = 1000;
@@ -163,11 +163,15 @@ export class DesktopAdapter implements SurrealistAdapter {
 	}
 
 	public async validateQuery(query: string) {
-		return invoke<string | null>("validate_query", { query });
+		const diagnostic = await invoke<Diagnostic | null>("validate_query", { query });
+
+		return diagnostic?.message ?? null;
 	}
 
 	public async validateWhereClause(clause: string) {
-		return invoke<boolean>("validate_where_clause", { clause });
+		const diagnostic = await invoke<Diagnostic | null>("validate_where_clause", { clause });
+
+		return diagnostic === null;
 	}
 
 	public openSurreal(options: SurrealOptions): SurrealHandle {
@@ -177,13 +181,14 @@ export class DesktopAdapter implements SurrealistAdapter {
 
 		this.#connecting = true;
 
+		const connectionId = newId();
 		const connection: any = mapKeys(options.connection, key => snake(key));
 		const details = {
 			...connection,
 			endpoint: connection.endpoint.replace("http", "ws")
 		};
 
-		invoke<any>('open_connection', { info: details }).then(() => {
+		invoke<any>('open_connection', { connectionId, info: details }).then(() => {
 			options.onConnect?.();
 		}).catch(err => {
 			console.error('Failed to open connection', err);
@@ -193,20 +198,30 @@ export class DesktopAdapter implements SurrealistAdapter {
 			this.#connecting = false;
 		});
 
+		const runningQueries = new Set<string>();
+
 		const execQuery = async (query: string, params: any) => {
 			console.log('Executing:', query, params);
 
+			const queryId = newId();
 			const maxTime = store.getState().config.queryTimeout;
-			const res = await invoke<any>('execute_query', { query, params, maxTime });
 
-			console.log('Result:', res);
+			runningQueries.add(queryId);
+
+			try {
+				const res = await invoke<any>('execute_query', { connectionId, queryId, query, variables: params, maxTime });
 
-			return JSON.parse(res);
+				console.log('Result:', res);
+
+				return JSON.parse(res);
+			} finally {
+				runningQueries.delete(queryId);
+			}
 		};
 
 		const handle: SurrealHandle = {
 			close: () => {
-				invoke<void>('close_connection');
+				invoke<void>('close_connection', { connectionId });
 				options.onDisconnect?.(1000, 'Closed by user');
 			},
 			query: async (query, params) => {
@@ -222,6 +237,13 @@ export class DesktopAdapter implements SurrealistAdapter {
 					};
 				});
 			},
+			cancel: async () => {
+				const cancelled = await Promise.all([...runningQueries].map(queryId => {
+					return invoke<boolean>('cancel_query', { queryId });
+				}));
+
+				return cancelled.includes(true);
+			},
 		};
 
 		this.#instance = handle;
@@ -61,6 +61,7 @@ export function Scaffold() {
 	const tabInfo = useActiveTab();
 
 	const [isConnecting, setIsConnecting] = useState(false);
+	const [isQuerying, setIsQuerying] = useState(false);
 	const [isViewListing, setIsViewListing] = useState(false);
 
 	const setIsConnected = useStable((value: boolean) => {
@@ -130,6 +131,8 @@ export function Scaffold() {
 		const { query, name } = tabInfo!;
 		const variables = tabInfo!.variables ? JSON.parse(tabInfo!.variables) : undefined;
 
+		setIsQuerying(true);
+
 		try {
 			const response = await adapter.getSurreal()?.query(override?.trim() || query, variables);
 
@@ -151,6 +154,8 @@ export function Scaffold() {
 					],
 				})
 			);
+		} finally {
+			setIsQuerying(false);
 		}
 
 		store.dispatch(
@@ -165,6 +170,18 @@ export function Scaffold() {
 		await updateConfig();
 	});
 
+	const cancelQuery = useStable(async (e: MouseEvent) => {
+		e.stopPropagation();
+
+		const cancelled = await adapter.getSurreal()?.cancel();
+
+		if (!cancelled) {
+			showNotification({
+				message: "The query could not be cancelled",
+			});
+		}
+	});
+
 	const closeConnection = useStable((e?: MouseEvent) => {
 		e?.stopPropagation();
 		adapter.getSurreal()?.close();
@@ -404,7 +421,15 @@ export function Scaffold() {
 							{detailsValid && (
 								<>
 									{isConnected ? (
-										viewMode == "query" && (
+										viewMode == "query" && (isQuerying ? (
+											<Button
+												color="red"
+												onClick={cancelQuery}
+												className={classes.sendButton}
+												title="Cancel Query">
+												Cancel Query
+											</Button>
+										) : (
 											<Button
 												color="surreal"
 												onClick={handleSendQuery}
@@ -412,7 +437,7 @@ export function Scaffold() {
 												title="Send Query (F9)">
 												Send Query
 											</Button>
-										)
+										))
 									) : (
 										<Button color="light" className={classes.sendButton} onClick={openConnection}>
 											{isConnecting ? "Connecting..." : "Connect"}
@@ -1,7 +1,8 @@
 import { ColorScheme } from "@mantine/core";
 
-export type AuthMode = "none" | "root" | "namespace" | "database" | "scope";
+export type AuthMode = "none" | "root" | "namespace" | "database" | "scope" | "token";
 export type DriverType = "file" | "memory" | "tikv";
+export type Protocol = "ws" | "wss" | "http" | "https" | "mem" | "file";
 export type QueryListing = "history" | "favorites";
 export type ResultListing = "table" | "json";
 export type ViewMode = "query" | "explorer" | "visualizer" | "designer" | "auth";
@@ -65,6 +66,14 @@ export interface SurrealistTab {
 	designerLayoutMode?: DesignerLayoutMode;
 }
 
+export interface Diagnostic {
+	message: string;
+	line: number;
+	column: number;
+	snippet: string;
+	expected: string[];
+}
+
 export interface ScopeField {
 	subject: string;
 	value: string;
@@ -156,11 +165,14 @@ export interface ConnectionOptions {
 	namespace: string;
 	database: string;
 	endpoint: string;
+	protocol?: Protocol;
 	username: string;
 	password: string;
 	authMode: AuthMode;
+	token?: string;
 	scope: string;
 	scopeFields: ScopeField[];
+	readOnly?: boolean;
 }
 
 export interface SurrealOptions {
@@ -174,6 +186,7 @@ export interface SurrealHandle {
 	close(): void;
 	query(query: string, params?: Record<string, any>): Promise<any>;
 	querySingle(query: string): Promise<any>;
+	cancel(): Promise<boolean>;
 }
 
 export interface TablePinAction {
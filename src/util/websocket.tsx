@@ -168,9 +168,17 @@ export function createLocalWebSocket(options: SurrealOptions): SurrealHandle {
 		options.onError?.(e.error);
 	});
 
+	/**
+	 * Queries sent over the socket run until they complete or time out
+	 */
+	const cancel = async () => {
+		return false;
+	};
+
 	return {
 		close,
 		query,
-		querySingle
+		querySingle,
+		cancel
 	};
 }
@@ -63,7 +63,7 @@ export function ResultPane() {
 	const listingIcon = resultListing == "table" ? mdiCodeJson : mdiTable;
 	const listingTitle = resultListing == "table" ? "Switch to JSON view" : "Switch to table view";
 
-	const showDivider = result?.result?.length > 0 || result?.time;
+	const showDivider = result?.result?.length > 0 || result?.time || result?.batch_time;
 
 	return (
 		<Panel
@@ -102,6 +102,14 @@ export function ResultPane() {
 							</Text>
 						</>
 					)}
+					{!result?.time && result?.batch_time && (
+						<>
+							<Icon color="light.4" path={mdiClock} mr={-10} />
+							<Text color="light.4" lineClamp={1} title="Statement times are not available, this is the time of the whole query">
+								{result.batch_time} total
+							</Text>
+						</>
+					)}
 				</Group>
 			}>
 			<div